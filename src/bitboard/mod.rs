@@ -0,0 +1,197 @@
+use crate::*;
+
+/// A set of squares, one bit per square: a1 is bit 0, b1 bit 1 and so on up to h8 as bit 63.
+pub type Bitboard = u64;
+
+pub const RANK_1: Bitboard = 0xff;
+pub const RANK_8: Bitboard = 0xff << 56;
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+
+pub fn square(position: Position) -> usize {
+    position.y * 8 + position.x
+}
+
+pub fn position(square: usize) -> Position {
+    (square % 8, square / 8).into()
+}
+
+pub fn bit(position: Position) -> Bitboard {
+    1 << square(position)
+}
+
+/// The squares in `bitboard`, lowest first.
+pub fn squares(mut bitboard: Bitboard) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let square = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        Some(square)
+    })
+}
+
+const fn leaper_attacks(deltas: &[(i32, i32)]) -> [Bitboard; 64] {
+    let mut attacks = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (x, y) = ((square % 8) as i32, (square / 8) as i32);
+        let mut i = 0;
+        while i < deltas.len() {
+            let (to_x, to_y) = (x + deltas[i].0, y + deltas[i].1);
+            if to_x >= 0 && to_x < 8 && to_y >= 0 && to_y < 8 {
+                attacks[square] |= 1 << (to_y * 8 + to_x);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    attacks
+}
+
+pub const KNIGHT_ATTACKS: [Bitboard; 64] = leaper_attacks(
+    &[(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)]
+);
+pub const KING_ATTACKS: [Bitboard; 64] = leaper_attacks(
+    &[(1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1)]
+);
+/// Squares a pawn of each color attacks from a square, indexed by color first.
+pub const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    leaper_attacks(&[(-1, 1), (1, 1)]),
+    leaper_attacks(&[(-1, -1), (1, -1)]),
+];
+
+/// Every square along `direction` from each square and its opposite, the square itself excluded.
+const fn line_masks(direction: (i32, i32)) -> [Bitboard; 64] {
+    let mut masks = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (x, y) = ((square % 8) as i32, (square / 8) as i32);
+        let mut sign = -1;
+        while sign <= 1 {
+            let (mut to_x, mut to_y) = (x + sign * direction.0, y + sign * direction.1);
+            while to_x >= 0 && to_x < 8 && to_y >= 0 && to_y < 8 {
+                masks[square] |= 1 << (to_y * 8 + to_x);
+                to_x += sign * direction.0;
+                to_y += sign * direction.1;
+            }
+            sign += 2;
+        }
+        square += 1;
+    }
+    masks
+}
+
+const FILE_MASKS: [Bitboard; 64] = line_masks((0, 1));
+const DIAGONAL_MASKS: [Bitboard; 64] = line_masks((1, 1));
+const ANTI_DIAGONAL_MASKS: [Bitboard; 64] = line_masks((1, -1));
+
+/// Attacks of a rook on the first rank for each file and each occupancy of that rank.
+const FIRST_RANK_ATTACKS: [[u8; 256]; 8] = {
+    let mut attacks = [[0; 256]; 8];
+    let mut file = 0;
+    while file < 8 {
+        let mut occupied = 0;
+        while occupied < 256 {
+            let mut to = file + 1;
+            while to < 8 {
+                attacks[file][occupied] |= 1 << to;
+                if occupied & (1 << to) != 0 {
+                    break;
+                }
+                to += 1;
+            }
+            let mut to = file as i32 - 1;
+            while to >= 0 {
+                attacks[file][occupied] |= 1 << to;
+                if occupied & (1 << to) != 0 {
+                    break;
+                }
+                to -= 1;
+            }
+            occupied += 1;
+        }
+        file += 1;
+    }
+    attacks
+};
+
+/// Hyperbola quintessence: sliding attacks along a line with at most one square per rank, using
+/// `o - 2r` on the occupancy and on its byte-swapped mirror for the two directions.
+fn line_attacks(square: usize, occupied: Bitboard, mask: Bitboard) -> Bitboard {
+    let slider = 1u64 << square;
+    let forward = occupied & mask;
+    let reverse = forward.swap_bytes();
+    let forward = forward.wrapping_sub(slider.wrapping_mul(2));
+    let reverse = reverse.wrapping_sub(slider.swap_bytes().wrapping_mul(2));
+    (forward ^ reverse.swap_bytes()) & mask
+}
+
+fn rank_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    let shift = square & 56;
+    (FIRST_RANK_ATTACKS[square & 7][((occupied >> shift) & 0xff) as usize] as Bitboard) << shift
+}
+
+pub fn bishop_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    line_attacks(square, occupied, DIAGONAL_MASKS[square]) |
+        line_attacks(square, occupied, ANTI_DIAGONAL_MASKS[square])
+}
+
+pub fn rook_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    line_attacks(square, occupied, FILE_MASKS[square]) | rank_attacks(square, occupied)
+}
+
+pub fn queen_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    bishop_attacks(square, occupied) | rook_attacks(square, occupied)
+}
+
+/// Squares attacked by a piece of `piece_type` and `color` on `square`.
+pub fn attacks(piece_type: PieceType, color: u8, square: usize, occupied: Bitboard) -> Bitboard {
+    match piece_type {
+        PieceType::Pawn => PAWN_ATTACKS[color as usize][square],
+        PieceType::Knight => KNIGHT_ATTACKS[square],
+        PieceType::Bishop => bishop_attacks(square, occupied),
+        PieceType::Rook => rook_attacks(square, occupied),
+        PieceType::Queen => queen_attacks(square, occupied),
+        PieceType::King => KING_ATTACKS[square],
+        PieceType::None => 0,
+    }
+}
+
+/// The board as one bitboard per piece type and one per color, kept in step with the mailbox
+/// array by `Game`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Bitboards {
+    /// Indexed by `PieceType as usize`, so entry 0 stays empty.
+    pub pieces: [Bitboard; 7],
+    pub colors: [Bitboard; 2],
+}
+
+impl Bitboards {
+    pub fn from_board(board: &[[Piece; 8]; 8]) -> Self {
+        let mut bitboards = Bitboards::default();
+        for (row_index, row) in board.iter().enumerate() {
+            for (x, piece) in row.iter().enumerate() {
+                bitboards.toggle(*piece, (7 - row_index) * 8 + x);
+            }
+        }
+        bitboards
+    }
+
+    /// Adds `piece` on `square`, or removes it if it is already there.
+    pub fn toggle(&mut self, piece: Piece, square: usize) {
+        if piece.is_empty() {
+            return;
+        }
+        self.pieces[piece.piece_type() as usize] ^= 1 << square;
+        self.colors[piece.color() as usize] ^= 1 << square;
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    pub fn of(&self, color: u8, piece_type: PieceType) -> Bitboard {
+        self.pieces[piece_type as usize] & self.colors[color as usize]
+    }
+}
@@ -0,0 +1,43 @@
+use crate::*;
+
+/// Why a move could not be read or played.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    /// The text is not a move in any notation we accept.
+    Parse(&'static str),
+    /// A long algebraic move names a start square without a piece of the side to move.
+    NoPiece(Position),
+    /// No piece of the described type can move to the square.
+    Unreachable(Position),
+    /// More than one piece fits the description; the move needs a file or rank hint.
+    Ambiguous(Position),
+    /// The piece on this square can't move because it is pinned to its king.
+    Pinned(Position),
+    /// The move would leave or put the mover's own king in check.
+    LeavesKingInCheck,
+    /// Castling while in check, or with the king crossing or landing on an attacked square.
+    CastlingThroughCheck,
+    /// The castling right is gone, or the king and rook aren't free to castle.
+    CastlingNotAllowed,
+    /// The side to move has no legal moves left.
+    GameOver,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        match self {
+            MoveError::Parse(reason) => write!(f, "{}", reason),
+            MoveError::NoPiece(position) => write!(f, "No piece to move on {}", position),
+            MoveError::Unreachable(position) => write!(f, "No piece can move to {}", position),
+            MoveError::Ambiguous(position) =>
+                write!(f, "More than one piece can move to {}, say which one", position),
+            MoveError::Pinned(position) => write!(f, "The piece on {} is pinned", position),
+            MoveError::LeavesKingInCheck => write!(f, "That move leaves the king in check"),
+            MoveError::CastlingThroughCheck => write!(f, "Cannot castle out of or through check"),
+            MoveError::CastlingNotAllowed => write!(f, "Castling is not allowed"),
+            MoveError::GameOver => write!(f, "The game is already over"),
+        }
+    }
+}
+
+impl Error for MoveError {}
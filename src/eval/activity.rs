@@ -0,0 +1,71 @@
+use crate::*;
+
+const PIECES: [PieceType; 4] = [
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+];
+
+/// Pseudo-legal moves of every knight, bishop, rook and queen, weighted by piece type.
+pub(super) fn mobility(game: &Game, weights: &Weights) -> Term {
+    let bitboards = game.bitboards();
+    let occupied = bitboards.occupied();
+    let mut term = Term::default();
+    for color in 0..2u8 {
+        let own = bitboards.colors[color as usize];
+        for piece_type in PIECES {
+            for square in squares(bitboards.of(color, piece_type)) {
+                let moves = (attacks(piece_type, color, square, occupied) & !own).count_ones();
+                let weight = weights.mobility[piece_type as usize];
+                term.add(color, Score::new(weight.mg * (moves as i32), weight.eg * (moves as i32)));
+            }
+        }
+    }
+    term
+}
+
+/// Pawn shield and open files around each king, less the pressure enemy pieces put on the
+/// squares next to it.
+pub(super) fn king_safety(game: &Game, weights: &Weights) -> Term {
+    let bitboards = game.bitboards();
+    let occupied = bitboards.occupied();
+    let mut term = Term::default();
+    for color in 0..2u8 {
+        let king = square(game.king_positions[color as usize]);
+        let (x, y) = (king % 8, king / 8);
+        let own_pawns = bitboards.of(color, PieceType::Pawn);
+        let enemy_pawns = bitboards.of(color ^ 1, PieceType::Pawn);
+
+        for file_x in x.saturating_sub(1)..=(x + 1).min(7) {
+            let file = FILE_A << file_x;
+            for (distance, bonus) in [(1, weights.shield_close), (2, weights.shield_far)] {
+                let rank_y = if color == 0 { y + distance } else { y.wrapping_sub(distance) };
+                if rank_y < 8 && own_pawns & file & (RANK_1 << (8 * rank_y)) != 0 {
+                    term.add(color, bonus);
+                }
+            }
+            if own_pawns & file == 0 {
+                term.add(
+                    color,
+                    if enemy_pawns & file == 0 { weights.open_file } else { weights.half_open_file }
+                );
+            }
+        }
+
+        let zone = KING_ATTACKS[king] | (1 << king);
+        let (mut attackers, mut weight) = (0, 0);
+        for piece_type in PIECES {
+            for square in squares(bitboards.of(color ^ 1, piece_type)) {
+                let hits = attacks(piece_type, color ^ 1, square, occupied) & zone;
+                if hits != 0 {
+                    attackers += 1;
+                    weight += weights.king_attack[piece_type as usize] * (hits.count_ones() as i32);
+                }
+            }
+        }
+        let danger = (weight * weights.attacker_scale[attackers.min(7)]) / 100;
+        term.add(color, Score::new(-danger, 0));
+    }
+    term
+}
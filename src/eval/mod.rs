@@ -1,27 +1,280 @@
 use crate::*;
+use activity::*;
+pub use pawns::*;
+use pst::*;
+use std::ops::{ Add, AddAssign, Neg, Sub, SubAssign };
+pub use weights::*;
 
+mod activity;
+mod pawns;
+mod pst;
+mod weights;
+
+/// Game phase with every knight, bishop, rook and queen still on the board.
+pub const MAX_PHASE: i32 = 24;
+
+/// A score kept as separate middlegame and endgame parts until the game phase is known.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Score { mg, eg }
+    }
+
+    /// Blends the two parts, all middlegame at `MAX_PHASE` and all endgame at 0.
+    pub fn taper(self, phase: i32) -> i32 {
+        let phase = phase.clamp(0, MAX_PHASE);
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, rhs: Score) -> Score {
+        Score::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Score) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, rhs: Score) -> Score {
+        Score::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, rhs: Score) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score::new(-self.mg, -self.eg)
+    }
+}
+
+impl std::iter::Sum for Score {
+    fn sum<I: Iterator<Item = Score>>(iter: I) -> Score {
+        iter.fold(Score::default(), Add::add)
+    }
+}
+
+/// Bonus for having the move.
+pub const TEMPO: Score = Score::new(20, 10);
+
+/// One evaluation term split by color. The total is white's part minus black's.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Term {
+    pub white: Score,
+    pub black: Score,
+}
+
+impl Term {
+    pub fn add(&mut self, color: u8, score: Score) {
+        if color == 0 {
+            self.white += score;
+        } else {
+            self.black += score;
+        }
+    }
+
+    pub fn total(&self) -> Score {
+        self.white - self.black
+    }
+}
+
+/// Breakdown of the evaluation of a position, see `Eval::explain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Eval {
+    /// Final score in centipawns from the point of view of the side to move.
     pub value: i32,
+    pub phase: i32,
+    pub turn: u8,
+    pub material: Term,
+    pub piece_square: Term,
+    pub pawn_structure: Term,
+    pub mobility: Term,
+    pub king_safety: Term,
+    pub tempo: Term,
+}
+
+impl Eval {
+    /// Evaluates `game` term by term, so the score can be shown with the reasons behind it.
+    pub fn explain(game: &Game) -> Eval {
+        Eval::build(game, PawnEntry::new(game), &Weights::DEFAULT)
+    }
+
+    /// Like `explain`, with the pawn structure looked up in `pawn_table`.
+    pub fn explain_with(game: &Game, pawn_table: &mut PawnTable) -> Eval {
+        Eval::build(game, pawn_table.probe(game), &Weights::DEFAULT)
+    }
+
+    /// Like `explain`, with other mobility and king safety weights, e.g. while tuning them.
+    pub fn explain_weighted(game: &Game, weights: &Weights) -> Eval {
+        Eval::build(game, PawnEntry::new(game), weights)
+    }
+
+    fn build(game: &Game, pawns: PawnEntry, weights: &Weights) -> Eval {
+        let mut eval = Eval {
+            value: 0,
+            phase: game_phase(game),
+            turn: game.turn,
+            material: Term::default(),
+            piece_square: Term::default(),
+            pawn_structure: pawns.term(game),
+            mobility: mobility(game, weights),
+            king_safety: king_safety(game, weights),
+            tempo: Term::default(),
+        };
+
+        for piece in game.get_board().iter().flatten().filter(|piece| !piece.is_empty()) {
+            let piece_type = PieceType::from(piece.byte);
+            eval.material.add(piece.color(), piece_type.value());
+            eval.piece_square.add(
+                piece.color(),
+                piece_square(piece_type, piece.color(), piece.position)
+            );
+        }
+        eval.tempo.add(game.turn, TEMPO);
+
+        let value = eval.total().taper(eval.phase);
+        eval.value = if game.turn == 0 { value } else { -value };
+        eval
+    }
+
+    /// The terms in the order they are shown.
+    pub fn terms(&self) -> [(&'static str, Term); 6] {
+        [
+            ("Material", self.material),
+            ("Piece-square", self.piece_square),
+            ("Pawn structure", self.pawn_structure),
+            ("Mobility", self.mobility),
+            ("King safety", self.king_safety),
+            ("Tempo", self.tempo),
+        ]
+    }
+
+    /// Sum of all terms from white's point of view, before tapering.
+    pub fn total(&self) -> Score {
+        self.terms()
+            .iter()
+            .map(|(_, term)| term.total())
+            .sum()
+    }
 }
 
+/// Something that contributes to the evaluation, from white's point of view.
 pub trait Value {
-    fn value(&self) -> i32;
+    fn value(&self) -> Score;
+
+    /// `value` tapered for a game `phase` between 0 and `MAX_PHASE`.
+    fn value_at(&self, phase: i32) -> i32 {
+        self.value().taper(phase)
+    }
 }
 
+/// A table in the style of Stockfish's `eval` command, in centipawns.
 impl Display for Eval {
     fn fmt(&self, f: &mut Formatter) -> fmtResult {
-        write!(f, "Evaluation: {}", self.value)
+        let line = "----------------+-------------+-------------+-------------";
+        let columns = |score: Score| format!("{:>5} {:>5}", score.mg, score.eg);
+        writeln!(f, "{:>15} |    White    |    Black    |    Total", "Term")?;
+        writeln!(f, "{:>15} |   MG    EG  |   MG    EG  |   MG    EG", "")?;
+        writeln!(f, "{}", line)?;
+        for (name, term) in self.terms() {
+            writeln!(
+                f,
+                "{:>15} | {} | {} | {}",
+                name,
+                columns(term.white),
+                columns(term.black),
+                columns(term.total())
+            )?;
+        }
+        writeln!(f, "{}", line)?;
+        writeln!(f, "{:>15} | {:>11} | {:>11} | {}", "Total", "", "", columns(self.total()))?;
+        writeln!(f)?;
+        writeln!(f, "Phase: {}/{}", self.phase, MAX_PHASE)?;
+        writeln!(f, "Evaluation: {} ({} to move)", self.value, if self.turn == 0 {
+            "white"
+        } else {
+            "black"
+        })
     }
 }
 
 impl From<&&mut Game> for Eval {
     fn from(game: &&mut Game) -> Eval {
-        let value = game
-            .get_board()
-            .iter()
-            .flatten()
-            .map(|piece| piece.value())
-            .sum();
-        Eval { value }
+        Eval::explain(game)
+    }
+}
+
+impl Value for PieceType {
+    /// Material value of the piece type. The king's is zero, as it never leaves the board.
+    fn value(&self) -> Score {
+        match self {
+            PieceType::Pawn => Score::new(82, 94),
+            PieceType::Knight => Score::new(337, 281),
+            PieceType::Bishop => Score::new(365, 297),
+            PieceType::Rook => Score::new(477, 512),
+            PieceType::Queen => Score::new(1025, 936),
+            PieceType::King | PieceType::None => Score::default(),
+        }
     }
 }
+
+/// Piece-square bonus for a piece of `piece_type` and `color` standing on `position`.
+pub fn piece_square(piece_type: PieceType, color: u8, position: Position) -> Score {
+    let (mg, eg) = match piece_type {
+        PieceType::Pawn => (&PAWN_MG, &PAWN_EG),
+        PieceType::Knight => (&KNIGHT_MG, &KNIGHT_EG),
+        PieceType::Bishop => (&BISHOP_MG, &BISHOP_EG),
+        PieceType::Rook => (&ROOK_MG, &ROOK_EG),
+        PieceType::Queen => (&QUEEN_MG, &QUEEN_EG),
+        PieceType::King => (&KING_MG, &KING_EG),
+        PieceType::None => {
+            return Score::default();
+        }
+    };
+    let rank = if color == 0 { 7 - position.y } else { position.y };
+    let index = rank * 8 + position.x;
+    Score::new(mg[index], eg[index])
+}
+
+/// How far the game is from the endgame, from the pieces other than pawns and kings left on
+/// the board: `MAX_PHASE` at the start, 0 with only pawns and kings.
+pub fn game_phase(game: &Game) -> i32 {
+    let bitboards = game.bitboards();
+    let count = |piece_type: PieceType| bitboards.pieces[piece_type as usize].count_ones() as i32;
+    let phase =
+        count(PieceType::Knight) +
+        count(PieceType::Bishop) +
+        count(PieceType::Rook) * 2 +
+        count(PieceType::Queen) * 4;
+    phase.min(MAX_PHASE)
+}
+
+/// Score of `game` in centipawns from the point of view of the side to move, as search wants it.
+pub fn evaluate(game: &Game) -> i32 {
+    Eval::explain(game).value
+}
+
+/// `evaluate` with the pawn structure cached in `pawn_table`.
+pub fn evaluate_with(game: &Game, pawn_table: &mut PawnTable) -> i32 {
+    Eval::explain_with(game, pawn_table).value
+}
@@ -0,0 +1,176 @@
+use crate::*;
+
+const DOUBLED: Score = Score::new(-10, -25);
+const ISOLATED: Score = Score::new(-5, -15);
+const BACKWARD: Score = Score::new(-9, -22);
+
+// Indexed by rank from the pawn owner's side, 0 being its back rank
+const CONNECTED: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(4, 4),
+    Score::new(6, 6),
+    Score::new(10, 10),
+    Score::new(20, 18),
+    Score::new(35, 30),
+    Score::new(60, 50),
+    Score::new(0, 0),
+];
+const PASSED: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(10, 15),
+    Score::new(15, 25),
+    Score::new(30, 50),
+    Score::new(50, 90),
+    Score::new(80, 140),
+    Score::new(0, 0),
+];
+/// Extra for a passed pawn with nothing at all standing between it and promotion.
+const PASSED_FREE_PATH: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(0, 5),
+    Score::new(0, 8),
+    Score::new(0, 12),
+    Score::new(5, 25),
+    Score::new(10, 45),
+    Score::new(20, 70),
+    Score::new(0, 0),
+];
+const CANDIDATE: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(3, 5),
+    Score::new(5, 8),
+    Score::new(8, 12),
+    Score::new(15, 25),
+    Score::new(25, 45),
+    Score::new(0, 0),
+    Score::new(0, 0),
+];
+
+/// 2^14 entries, about 640 KB.
+pub const DEFAULT_PAWN_TABLE_ENTRIES: usize = 1 << 14;
+
+fn file(x: usize) -> Bitboard {
+    FILE_A << x
+}
+
+fn adjacent_files(x: usize) -> Bitboard {
+    ((file(x) << 1) & !FILE_A) | ((file(x) >> 1) & !(FILE_A << 7))
+}
+
+/// Ranks strictly in front of rank `y` as seen by `color`.
+fn ranks_ahead(color: u8, y: usize) -> Bitboard {
+    if color == 0 {
+        if y == 7 { 0 } else { !0 << (8 * (y + 1)) }
+    } else {
+        (1 << (8 * y)) - 1
+    }
+}
+
+fn relative_rank(color: u8, y: usize) -> usize {
+    if color == 0 { y } else { 7 - y }
+}
+
+/// The pawn-only part of the pawn structure evaluation, which only depends on `key`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct PawnEntry {
+    pub key: u64,
+    pub scores: [Score; 2],
+    pub passed: [Bitboard; 2],
+}
+
+impl PawnEntry {
+    pub fn new(game: &Game) -> Self {
+        let mut entry = PawnEntry { key: game.pawn_key(), ..Default::default() };
+        for color in 0..2u8 {
+            let own = game.bitboards().of(color, PieceType::Pawn);
+            let theirs = game.bitboards().of(color ^ 1, PieceType::Pawn);
+            for pawn in squares(own) {
+                let Position { x, y } = position(pawn);
+                let rank = relative_rank(color, y);
+                let ahead = ranks_ahead(color, y);
+                let front_span = file(x) & ahead;
+                let neighbours = own & adjacent_files(x);
+                let sentries = theirs & adjacent_files(x) & ahead;
+                let helpers = neighbours & !ahead;
+                let supported = own & PAWN_ATTACKS[(color ^ 1) as usize][pawn] != 0;
+                let phalanx = neighbours & (RANK_1 << (8 * y)) != 0;
+                let stop = if color == 0 { pawn + 8 } else { pawn - 8 };
+                let score = &mut entry.scores[color as usize];
+
+                if own & front_span != 0 {
+                    *score += DOUBLED;
+                }
+                if neighbours == 0 {
+                    *score += ISOLATED;
+                } else if helpers == 0 && PAWN_ATTACKS[color as usize][stop] & theirs != 0 {
+                    *score += BACKWARD;
+                }
+                if supported || phalanx {
+                    *score += CONNECTED[rank];
+                }
+
+                if theirs & front_span == 0 {
+                    if sentries == 0 {
+                        if own & front_span == 0 {
+                            entry.passed[color as usize] |= 1 << pawn;
+                            *score += PASSED[rank];
+                        }
+                    } else if helpers.count_ones() >= sentries.count_ones() {
+                        *score += CANDIDATE[rank];
+                    }
+                }
+            }
+        }
+        entry
+    }
+
+    /// Pawn structure term for the position, adding to the cached part what depends on other
+    /// pieces: whether each passed pawn's path to promotion is clear.
+    pub fn term(&self, game: &Game) -> Term {
+        let mut term = Term { white: self.scores[0], black: self.scores[1] };
+        let occupied = game.bitboards().occupied();
+        for color in 0..2u8 {
+            for pawn in squares(self.passed[color as usize]) {
+                let Position { x, y } = position(pawn);
+                if file(x) & ranks_ahead(color, y) & occupied == 0 {
+                    term.add(color, PASSED_FREE_PATH[relative_rank(color, y)]);
+                }
+            }
+        }
+        term
+    }
+}
+
+/// Cache of pawn structure evaluations keyed on `Game::pawn_key`. Pawn structures change far
+/// less often than positions do, so during search nearly every probe hits.
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl PawnTable {
+    pub fn new(entries: usize) -> Self {
+        PawnTable { entries: vec![PawnEntry::default(); entries.max(1)] }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnEntry::default());
+    }
+
+    /// The entry for the pawns of `game`, evaluating them on a miss. An empty slot holds key 0
+    /// with no scores, which is also the right entry for a board without pawns.
+    pub fn probe(&mut self, game: &Game) -> PawnEntry {
+        let key = game.pawn_key();
+        let index = (((key as u128) * (self.entries.len() as u128)) >> 64) as usize;
+        if self.entries[index].key != key {
+            self.entries[index] = PawnEntry::new(game);
+        }
+        self.entries[index]
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        PawnTable::new(DEFAULT_PAWN_TABLE_ENTRIES)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::*;
+
+/// Tunable weights of the mobility and king safety terms. `Weights::default()` is what
+/// `evaluate` uses; `Eval::explain_weighted` evaluates with any other set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Weights {
+    /// Bonus per pseudo-legal move, indexed by `PieceType as usize`.
+    pub mobility: [Score; 7],
+    /// Bonus per own pawn on the rank just in front of the king, on its file or a neighbour.
+    pub shield_close: Score,
+    /// Bonus per own pawn two ranks in front of the king.
+    pub shield_far: Score,
+    /// Penalty per file next to or under the king with no pawns at all.
+    pub open_file: Score,
+    /// Penalty per such file with only enemy pawns on it.
+    pub half_open_file: Score,
+    /// Weight per square of the king zone an enemy piece attacks, by `PieceType as usize`.
+    pub king_attack: [i32; 7],
+    /// Percentage of the summed attack weight counted, by number of attacking pieces. A lone
+    /// attacker is rarely dangerous, so it counts for nothing.
+    pub attacker_scale: [i32; 8],
+}
+
+impl Weights {
+    pub const DEFAULT: Weights = Weights {
+        mobility: [
+            Score::new(0, 0),
+            Score::new(0, 0),
+            Score::new(2, 4),
+            Score::new(4, 4),
+            Score::new(5, 5),
+            Score::new(1, 2),
+            Score::new(0, 0),
+        ],
+        shield_close: Score::new(12, 0),
+        shield_far: Score::new(6, 0),
+        open_file: Score::new(-20, 0),
+        half_open_file: Score::new(-10, 0),
+        king_attack: [0, 0, 40, 20, 20, 80, 0],
+        attacker_scale: [0, 0, 50, 75, 88, 94, 97, 99],
+    };
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights::DEFAULT
+    }
+}
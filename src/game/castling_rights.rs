@@ -0,0 +1,86 @@
+use crate::Position;
+use std::fmt::{ Display, Formatter, Result as fmtResult };
+
+pub const WHITE_KINGSIDE: u8 = 0b0001;
+pub const WHITE_QUEENSIDE: u8 = 0b0010;
+pub const BLACK_KINGSIDE: u8 = 0b0100;
+pub const BLACK_QUEENSIDE: u8 = 0b1000;
+
+/// Castling availability packed as bit flags, in the same `KQkq` order as FEN.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CastlingRights(pub u8);
+
+impl CastlingRights {
+    pub fn all() -> Self {
+        CastlingRights(WHITE_KINGSIDE | WHITE_QUEENSIDE | BLACK_KINGSIDE | BLACK_QUEENSIDE)
+    }
+
+    pub fn none() -> Self {
+        CastlingRights(0)
+    }
+
+    /// The flag for `color` castling to the side given by a `PMove::castling` value.
+    pub fn flag(color: u8, castling: u8) -> u8 {
+        match (color, castling) {
+            (0, 1) => WHITE_KINGSIDE,
+            (0, 2) => WHITE_QUEENSIDE,
+            (1, 1) => BLACK_KINGSIDE,
+            (1, 2) => BLACK_QUEENSIDE,
+            _ => 0,
+        }
+    }
+
+    pub fn has(&self, flag: u8) -> bool {
+        (self.0 & flag) != 0
+    }
+
+    pub fn insert(&mut self, flag: u8) {
+        self.0 |= flag;
+    }
+
+    pub fn remove(&mut self, flag: u8) {
+        self.0 &= !flag;
+    }
+
+    pub fn remove_color(&mut self, color: u8) {
+        self.remove(if color == 0 {
+            WHITE_KINGSIDE | WHITE_QUEENSIDE
+        } else {
+            BLACK_KINGSIDE | BLACK_QUEENSIDE
+        });
+    }
+
+    /// Drops the rights that depend on the king or rook that starts on `position`, used whenever
+    /// a piece moves from or is captured on that square.
+    pub fn remove_for_square(&mut self, position: Position) {
+        match (position.x, position.y) {
+            (4, 0) => self.remove_color(0),
+            (4, 7) => self.remove_color(1),
+            (7, 0) => self.remove(WHITE_KINGSIDE),
+            (0, 0) => self.remove(WHITE_QUEENSIDE),
+            (7, 7) => self.remove(BLACK_KINGSIDE),
+            (0, 7) => self.remove(BLACK_QUEENSIDE),
+            _ => {}
+        }
+    }
+}
+
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        if self.0 == 0 {
+            return write!(f, "-");
+        }
+
+        for (flag, symbol) in [
+            (WHITE_KINGSIDE, 'K'),
+            (WHITE_QUEENSIDE, 'Q'),
+            (BLACK_KINGSIDE, 'k'),
+            (BLACK_QUEENSIDE, 'q'),
+        ] {
+            if self.has(flag) {
+                write!(f, "{}", symbol)?;
+            }
+        }
+        Ok(())
+    }
+}
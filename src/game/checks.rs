@@ -6,8 +6,8 @@ use std::{
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Checks(Vec<bool>);
 
-impl Checks {
-    pub fn default() -> Self {
+impl Default for Checks {
+    fn default() -> Self {
         Checks(vec![false, false])
     }
 }
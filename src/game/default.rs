@@ -3,7 +3,7 @@ use std::array;
 
 fn create_pawn_row(color: bool) -> [Piece; 8] {
     array::from_fn(|i| {
-        Piece::new(if color { 0b1001 } else { 0b0001 }, (i, if color { 6 } else { 1 }).into())
+        Piece::new(if color { 0b1001 } else { 0b0001 }, (i, if color { 6 } else { 1 }))
     })
 }
 
@@ -14,26 +14,38 @@ fn create_start_row(color: bool) -> [Piece; 8] {
         .fold([Piece::empty((0, 0).into()); 8], |mut pieces, (index, piece)| {
             pieces[index] = Piece::new(
                 ((color as u8) << 3) + piece,
-                (index, if !color { 0 } else { 7 }).into()
+                (index, if !color { 0 } else { 7 })
             );
             pieces
         })
 }
 
 fn create_empty_row(y: usize) -> [Piece; 8] {
-    array::from_fn(|i| Piece::new(0, (i, y).into()))
+    array::from_fn(|i| Piece::new(0, (i, y)))
 }
 
 impl Default for Game {
     fn default() -> Self {
-        Game {
-            board: create_board(),
+        let board = create_board();
+        let mut game = Game {
+            board,
+            bitboards: Bitboards::from_board(&board),
             turn: 0,
             check: Checks::default(),
             king_positions: [(4, 0).into(), (4, 7).into()],
             moves: Moves::new(),
+            hash: 0,
             hash_history: vec![],
-        }
+            castling_rights: CastlingRights::all(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            start_fen: None,
+            undo_history: vec![],
+        };
+        game.hash = game.zobrist();
+        game.hash_history.push(game.hash);
+        game
     }
 }
 
@@ -0,0 +1,309 @@
+use crate::*;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(&'static str),
+    TooManyFields,
+    WrongRankCount(usize),
+    BadRankLength {
+        rank: usize,
+        length: usize,
+    },
+    UnknownPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    KingCount {
+        color: u8,
+        count: usize,
+    },
+    PawnOnBackRank(Position),
+    OpponentInCheck,
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        let color_name = |color: &u8| if *color == 0 { "white" } else { "black" };
+        match self {
+            FenError::MissingField(field) => write!(f, "FEN is missing the {} field", field),
+            FenError::TooManyFields => write!(f, "FEN has more than six fields"),
+            FenError::WrongRankCount(count) => write!(f, "FEN has {} ranks instead of 8", count),
+            FenError::BadRankLength { rank, length } =>
+                write!(f, "Rank {} describes {} squares instead of 8", rank + 1, length),
+            FenError::UnknownPiece(c) => write!(f, "Unknown piece letter '{}'", c),
+            FenError::InvalidSideToMove(s) => write!(f, "Invalid side to move '{}'", s),
+            FenError::InvalidCastling(s) => write!(f, "Invalid castling field '{}'", s),
+            FenError::InvalidEnPassant(s) => write!(f, "Invalid en passant square '{}'", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "Invalid halfmove clock '{}'", s),
+            FenError::InvalidFullmoveNumber(s) => write!(f, "Invalid fullmove number '{}'", s),
+            FenError::KingCount { color, count } =>
+                write!(f, "Expected one {} king, found {}", color_name(color), count),
+            FenError::PawnOnBackRank(position) => write!(f, "Pawn on back rank at {}", position),
+            FenError::OpponentInCheck => write!(f, "The side not to move is in check"),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+fn piece_from_char(c: char, position: Position) -> Result<Piece, FenError> {
+    let piece_type = match c.to_ascii_lowercase() {
+        'p' => PieceType::Pawn,
+        'r' => PieceType::Rook,
+        'n' => PieceType::Knight,
+        'b' => PieceType::Bishop,
+        'q' => PieceType::Queen,
+        'k' => PieceType::King,
+        _ => {
+            return Err(FenError::UnknownPiece(c));
+        }
+    };
+    let color = c.is_ascii_lowercase() as u8;
+    Ok(Piece::from_position((color << 3) + (piece_type as u8), position))
+}
+
+fn piece_to_char(piece: &Piece) -> char {
+    let c = match piece.piece_type().into() {
+        PieceType::Pawn => 'p',
+        PieceType::Rook => 'r',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k',
+        PieceType::None => unreachable!(),
+    };
+    if piece.color() == 0 { c.to_ascii_uppercase() } else { c }
+}
+
+pub fn parse_square(s: &str) -> Option<Position> {
+    let mut chars = s.chars();
+    let x = match chars.next()? {
+        x @ 'a'..='h' => (x as usize) - ('a' as usize),
+        _ => {
+            return None;
+        }
+    };
+    let y = match chars.next()? {
+        y @ '1'..='8' => (y as usize) - ('1' as usize),
+        _ => {
+            return None;
+        }
+    };
+    if chars.next().is_some() {
+        return None;
+    }
+    Some((x, y).into())
+}
+
+fn parse_board(placement: &str) -> Result<[[Piece; 8]; 8], FenError> {
+    let rows = placement.split('/').collect::<Vec<_>>();
+    if rows.len() != 8 {
+        return Err(FenError::WrongRankCount(rows.len()));
+    }
+
+    let mut board = [[Piece::empty((0, 0).into()); 8]; 8];
+    for (row_index, row) in rows.iter().enumerate() {
+        let y = 7 - row_index;
+        let mut x = 0;
+        for c in row.chars() {
+            if let Some(skip) = c.to_digit(10).filter(|skip| (1..=8).contains(skip)) {
+                for _ in 0..skip {
+                    if x < 8 {
+                        board[row_index][x] = Piece::empty((x, y).into());
+                    }
+                    x += 1;
+                }
+            } else {
+                let piece = piece_from_char(c, (x.min(7), y).into())?;
+                if x < 8 {
+                    board[row_index][x] = piece;
+                }
+                x += 1;
+            }
+        }
+        if x != 8 {
+            return Err(FenError::BadRankLength { rank: y, length: x });
+        }
+    }
+    Ok(board)
+}
+
+fn parse_castling(field: &str) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::none();
+    if field == "-" {
+        return Ok(rights);
+    }
+
+    for c in field.chars() {
+        let flag = match c {
+            'K' => WHITE_KINGSIDE,
+            'Q' => WHITE_QUEENSIDE,
+            'k' => BLACK_KINGSIDE,
+            'q' => BLACK_QUEENSIDE,
+            _ => {
+                return Err(FenError::InvalidCastling(field.to_string()));
+            }
+        };
+        if rights.has(flag) {
+            return Err(FenError::InvalidCastling(field.to_string()));
+        }
+        rights.insert(flag);
+    }
+    Ok(rights)
+}
+
+impl Game {
+    /// Builds a game from a FEN string. The halfmove clock and fullmove number may be omitted,
+    /// in which case they default to `0` and `1`.
+    pub fn from_fen(fen: &str) -> Result<Game, FenError> {
+        let mut fields = fen.split_whitespace();
+        let board = parse_board(fields.next().ok_or(FenError::MissingField("piece placement"))?)?;
+
+        let turn = match fields.next().ok_or(FenError::MissingField("side to move"))? {
+            "w" => 0,
+            "b" => 1,
+            other => {
+                return Err(FenError::InvalidSideToMove(other.to_string()));
+            }
+        };
+
+        let castling_rights = parse_castling(
+            fields.next().ok_or(FenError::MissingField("castling"))?
+        )?;
+
+        let en_passant = match fields.next().ok_or(FenError::MissingField("en passant"))? {
+            "-" => None,
+            square =>
+                match parse_square(square) {
+                    Some(position) if position.y == (if turn == 0 { 5 } else { 2 }) => {
+                        Some(position)
+                    }
+                    _ => {
+                        return Err(FenError::InvalidEnPassant(square.to_string()));
+                    }
+                }
+        };
+
+        let halfmove_clock = match fields.next() {
+            Some(clock) =>
+                clock.parse().map_err(|_| FenError::InvalidHalfmoveClock(clock.to_string()))?,
+            None => 0,
+        };
+
+        let fullmove_number = match fields.next() {
+            Some(number) =>
+                match number.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => {
+                        return Err(FenError::InvalidFullmoveNumber(number.to_string()));
+                    }
+                }
+            None => 1,
+        };
+
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
+
+        let mut king_positions = [Position { x: 0, y: 0 }; 2];
+        for color in 0..2u8 {
+            let kings = board
+                .iter()
+                .flatten()
+                .filter(|piece| piece.byte == (color << 3) + (PieceType::King as u8))
+                .collect::<Vec<_>>();
+            if kings.len() != 1 {
+                return Err(FenError::KingCount { color, count: kings.len() });
+            }
+            king_positions[color as usize] = kings[0].position;
+        }
+
+        if
+            let Some(pawn) = board
+                .iter()
+                .flatten()
+                .find(|piece| {
+                    piece.piece_type() == (PieceType::Pawn as u8) &&
+                        (piece.position.y == 0 || piece.position.y == 7)
+                })
+        {
+            return Err(FenError::PawnOnBackRank(pawn.position));
+        }
+
+        let mut game = Game {
+            board,
+            bitboards: Bitboards::from_board(&board),
+            turn,
+            check: Checks::default(),
+            king_positions,
+            moves: Moves::new(),
+            hash: 0,
+            hash_history: vec![],
+            castling_rights,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            start_fen: None,
+            undo_history: vec![],
+        };
+
+        game.check = game.is_king_in_check();
+        if game.check[turn ^ 1] {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        game.hash = game.zobrist();
+        game.hash_history.push(game.hash);
+
+        let normalized = game.to_fen();
+        if normalized != START_FEN {
+            game.start_fen = Some(normalized);
+        }
+
+        Ok(game)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let placement = self.board
+            .iter()
+            .map(|row| {
+                let mut rank = String::new();
+                let mut empty = 0;
+                for piece in row.iter() {
+                    if piece.is_empty() {
+                        empty += 1;
+                        continue;
+                    }
+                    if empty > 0 {
+                        rank.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    rank.push(piece_to_char(piece));
+                }
+                if empty > 0 {
+                    rank.push_str(&empty.to_string());
+                }
+                rank
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            if self.turn == 0 {
+                "w"
+            } else {
+                "b"
+            },
+            self.castling_rights,
+            self.en_passant.map_or("-".to_string(), |position| position.to_string()),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
@@ -1,5 +1,5 @@
 use crate::*;
-use std::ops::{Index, IndexMut};
+use std::ops::Index;
 
 impl Index<Position> for Game {
     type Output = Piece;
@@ -12,15 +12,6 @@ impl Index<Position> for Game {
     }
 }
 
-impl IndexMut<Position> for Game {
-    fn index_mut(&mut self, position: Position) -> &mut Self::Output {
-        if !Game::in_bounds(position) {
-            return &mut self.board[0][0];
-        }
-        &mut self.board[7 - position.y][position.x]
-    }
-}
-
 impl<T> Index<(T, T)> for Game
 where
     T: Into<usize> + Copy + Clone,
@@ -31,12 +22,3 @@ where
         &self[Into::<Position>::into((x.into(), y.into()))]
     }
 }
-
-impl<T> IndexMut<(T, T)> for Game
-where
-    T: Into<usize> + Copy + Clone,
-{
-    fn index_mut(&mut self, (x, y): (T, T)) -> &mut Self::Output {
-        &mut self[Into::<Position>::into((x.into(), y.into()))]
-    }
-}
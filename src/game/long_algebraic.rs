@@ -0,0 +1,67 @@
+use crate::*;
+
+impl PMove {
+    /// Long algebraic (UCI) notation such as `e2e4` or `e7e8q`. The start square must be known,
+    /// as it is for generated moves and after `fill_start_position`.
+    pub fn to_uci(&self) -> String {
+        let promotion = match self.promotion() {
+            PieceType::Rook => "r",
+            PieceType::Knight => "n",
+            PieceType::Bishop => "b",
+            PieceType::Queen => "q",
+            _ => "",
+        };
+        format!("{}{}{}", self.start_position(), self.end_position(), promotion)
+    }
+}
+
+impl Game {
+    /// Reads a long algebraic (UCI) move against the current position, filling in the piece
+    /// type, capture flag and castling from the board. A pawn reaching the last rank has to name
+    /// its promotion, and no other move may. Legality is left to `make_pmove`.
+    pub fn parse_uci(&self, s: &str) -> Result<PMove, MoveError> {
+        let s = s.trim();
+        if !s.is_ascii() || s.len() < 4 || s.len() > 5 {
+            return Err(MoveError::Parse("Invalid move"));
+        }
+
+        let start = parse_square(&s[0..2]).ok_or(MoveError::Parse("Invalid start square"))?;
+        let end = parse_square(&s[2..4]).ok_or(MoveError::Parse("Invalid end square"))?;
+        let promotion = match &s[4..] {
+            "" => PieceType::None,
+            "q" => PieceType::Queen,
+            "r" => PieceType::Rook,
+            "n" => PieceType::Knight,
+            "b" => PieceType::Bishop,
+            _ => {
+                return Err(MoveError::Parse("Invalid promotion"));
+            }
+        };
+
+        let piece = self[start];
+        if piece.is_empty() || piece.color() != self.turn {
+            return Err(MoveError::NoPiece(start));
+        }
+        let piece_type = PieceType::from(piece.piece_type());
+
+        if piece_type == PieceType::King && start.y == end.y && start.x.abs_diff(end.x) == 2 {
+            let castling = if end.x > start.x { 1 } else { 2 };
+            return Ok(
+                PMove::new(start.to_byte(), end.to_byte(), piece_type as u8, false, castling, 0)
+            );
+        }
+
+        let is_capture =
+            !self[end].is_empty() || (piece_type == PieceType::Pawn && start.x != end.x);
+        let pmove = PMove::new(
+            start.to_byte(),
+            end.to_byte(),
+            piece_type as u8,
+            is_capture,
+            0,
+            promotion as u8
+        );
+        pmove.check_promotion(self.turn)?;
+        Ok(pmove)
+    }
+}
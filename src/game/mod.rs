@@ -1,17 +1,37 @@
 use crate::*;
 
+pub mod castling_rights;
 pub mod checks;
 pub mod default;
+pub mod fen;
 mod index_extensions;
+pub mod long_algebraic;
+pub mod movegen;
+pub mod perft;
+pub mod san;
+pub mod see;
+pub mod undo;
+pub mod zobrist;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Game {
     board: [[Piece; 8]; 8],
+    bitboards: Bitboards,
     pub turn: u8,
     pub check: Checks,
     pub king_positions: [Position; 2],
     pub moves: Moves,
-    pub hash_history: Vec<[u64; 4]>,
+    /// Zobrist key of the current position, see `Game::zobrist`.
+    pub hash: u64,
+    /// Keys of every position reached so far, starting with the initial one.
+    pub hash_history: Vec<u64>,
+    pub castling_rights: CastlingRights,
+    pub en_passant: Option<Position>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    /// FEN of the starting position, for games that didn't begin from the standard setup.
+    pub start_fen: Option<String>,
+    undo_history: Vec<UndoState>,
 }
 
 impl Display for Game {
@@ -46,127 +66,192 @@ impl GameResult {
     }
 }
 
-type MoveResult = Result<(), String>;
+type MoveResult = Result<(), MoveError>;
 
 impl Game {
     pub fn make_pmove(&mut self, mut pmove: PMove) -> MoveResult {
-        if !pmove.fill_start_position(self) {
-            return Err("Invalid move".to_string());
+        if let Err(error) = pmove.fill_start_position(self) {
+            return Err(if self.get_valid_moves().is_empty() { MoveError::GameOver } else { error });
         }
 
+        self.undo_history.push(self.undo_state(pmove));
+        let hash = self.hash ^ self.state_key() ^ self.move_key(pmove);
+        self.update_clocks(pmove);
+        self.update_castling_rights(pmove);
+        self.en_passant = self.en_passant_target(pmove);
+
         self.piece_pmove(pmove);
         if pmove.piece_type() == PieceType::King {
             self.update_king_pos(pmove.end_position());
         }
 
         self.moves.push(pmove);
-        self.hash_history.push(self.serialize_to_ints());
         self.check = self.is_king_in_check();
         self.turn ^= 1;
+        self.hash = hash ^ self.state_key();
+        self.hash_history.push(self.hash);
         Ok(())
     }
 
+    fn update_clocks(&mut self, pmove: PMove) {
+        if pmove.piece_type() == PieceType::Pawn || pmove.is_capture() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if self.turn == 1 {
+            self.fullmove_number += 1;
+        }
+    }
+
+    fn update_castling_rights(&mut self, pmove: PMove) {
+        if pmove.castling() != 0 {
+            self.castling_rights.remove_color(self.turn);
+        } else {
+            self.castling_rights.remove_for_square(pmove.start_position());
+            self.castling_rights.remove_for_square(pmove.end_position());
+        }
+    }
+
+    fn en_passant_target(&self, pmove: PMove) -> Option<Position> {
+        let (start, end) = (pmove.start_position(), pmove.end_position());
+        if pmove.piece_type() != PieceType::Pawn || start.y.abs_diff(end.y) != 2 {
+            return None;
+        }
+        Some(start.with_y((start.y + end.y) / 2))
+    }
+
     fn piece_pmove(&mut self, pmove: PMove) {
         if pmove.is_capture() && self[pmove.end_position()].is_empty() {
             self.empty(pmove.end_position().with_y(pmove.start_position().y));
         }
 
-        let final_piece = Piece::from_position(pmove.byte(self), pmove.end_position());
         if
             let Some((king_pos, rook_pos)) =
                 CASTLING[(self.turn * 2 + pmove.castling() * 3) as usize]
         {
-            let (king_mask, rook_mask) = match self.turn {
-                0 => (0b0110, 0b0010),
-                1 => (0b1110, 0b1010),
-                _ => unreachable!(),
-            };
-            self[king_pos] = Piece::from_position(king_mask, king_pos.into());
-            self[rook_pos] = Piece::from_position(rook_mask, rook_pos.into());
+            let back_rank = (self.turn as usize) * 7;
+            self.empty((4, back_rank));
+            self.empty((if pmove.castling() == 1 { 7 } else { 0 }, back_rank));
+            self.put(king_pos.into(), (self.turn << 3) + (PieceType::King as u8));
+            self.put(rook_pos.into(), (self.turn << 3) + (PieceType::Rook as u8));
         } else {
-            self[pmove.end_position()] = final_piece;
+            self.put(pmove.end_position(), pmove.byte(self));
             self.empty(pmove.start_position());
         }
     }
 
-    pub fn castling_allowed(&self, king_pos: (usize, usize), rook_pos: (usize, usize)) -> bool {
-        self.position_attacked(king_pos.into()) ||
-            self.position_attacked(rook_pos.into()) ||
-            self.is_king_in_check()[self.turn]
+    /// Whether the side to move may castle to the side given by a `PMove::castling` value.
+    pub fn castling_allowed(&self, castling: u8) -> bool {
+        self.check_castling(castling).is_ok()
+    }
+
+    /// Castling needs the right to still be held, king and rook at home with only empty squares
+    /// between them, and the king not in check and not crossing or landing on an attacked square.
+    pub fn check_castling(&self, castling: u8) -> Result<(), MoveError> {
+        let back_rank = (self.turn as usize) * 7;
+        let (rook_x, between, king_path) = match castling {
+            1 => (7, 5..7, 4..7),
+            2 => (0, 1..4, 2..5),
+            _ => {
+                return Err(MoveError::Parse("Invalid castling"));
+            }
+        };
+        if
+            !self.castling_rights.has(CastlingRights::flag(self.turn, castling)) ||
+            self[(4, back_rank)].byte != (self.turn << 3) + (PieceType::King as u8) ||
+            self[(rook_x, back_rank)].byte != (self.turn << 3) + (PieceType::Rook as u8) ||
+            between.into_iter().any(|x| !self[(x, back_rank)].is_empty())
+        {
+            return Err(MoveError::CastlingNotAllowed);
+        }
+        if king_path.into_iter().any(|x| self.position_attacked((x, back_rank).into())) {
+            return Err(MoveError::CastlingThroughCheck);
+        }
+        Ok(())
     }
 
     fn update_king_pos(&mut self, king_pos: Position) {
         self.king_positions[self.turn as usize] = king_pos;
     }
 
+    /// Places the piece `byte` on `position`, replacing whatever stood there. All changes to
+    /// the board go through here so the bitboards stay in step with it.
+    pub(crate) fn put(&mut self, position: Position, byte: u8) {
+        let square = square(position);
+        let board_square = &mut self.board[7 - position.y][position.x];
+        self.bitboards.toggle(*board_square, square);
+        *board_square = Piece::from_position(byte, position);
+        self.bitboards.toggle(*board_square, square);
+    }
+
     fn empty<T>(&mut self, position: T) where T: Into<Position> + Copy {
-        self[position.into()] = Piece::empty(position.into());
-    }
-
-    pub fn is_path(
-        &self,
-        path_type: PiecePath,
-        start_position: Position,
-        end_position: Position
-    ) -> bool {
-        match path_type {
-            PiecePath::Straight => {
-                if start_position.x == end_position.x {
-                    let range = if start_position.y < end_position.y {
-                        start_position.y + 1..end_position.y
-                    } else {
-                        end_position.y + 1..start_position.y
-                    };
-                    return range.step_by(1).all(|y| self[(start_position.x, y)].is_empty());
-                } else if start_position.y == end_position.y {
-                    let range = if start_position.x < end_position.x {
-                        start_position.x + 1..end_position.x
-                    } else {
-                        end_position.x + 1..start_position.x
-                    };
-                    return range.step_by(1).all(|x| self[(x, start_position.y)].is_empty());
-                }
-                false
-            }
-            PiecePath::Diagonal => {
-                if start_position.x == end_position.x || start_position.y == end_position.y {
-                    return false;
-                }
-                let (step_x, step_y) = (
-                    if start_position.x < end_position.x { 1 } else { -1 },
-                    if start_position.y < end_position.y { 1 } else { -1 },
-                );
-                let (mut x, mut y) = (
-                    (start_position.x as isize) + step_x,
-                    (start_position.y as isize) + step_y,
-                );
-                while
-                    (x, y) != end_position.into() &&
-                    Game::in_bounds((x as usize, y as usize).into())
-                {
-                    if !self[(x as usize, y as usize)].is_empty() {
-                        return false;
-                    }
-                    x += step_x;
-                    y += step_y;
-                }
-                true
-            }
-        }
+        self.put(position.into(), 0);
+    }
+
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
     }
 
     pub fn is_king_in_check(&self) -> Checks {
         self.king_positions
             .iter()
-            .map(|pos| self.position_attacked(*pos))
+            .enumerate()
+            .map(|(color, pos)| self.attacked_by(*pos, (color as u8) ^ 1))
             .collect()
     }
 
+    /// Whether the side not to move attacks `end_position`.
     pub fn position_attacked(&self, end_position: Position) -> bool {
-        PieceType::iter().any(|p_type| {
-            let mut p_move = PMove::partial(0, end_position.to_byte(), p_type as u8, true, 0, 0);
-            p_move.fill_start_position(self)
-        })
+        self.attacked_by(end_position, self.turn ^ 1)
+    }
+
+    pub fn attacked_by(&self, position: Position, color: u8) -> bool {
+        self.attackers_to(square(position), self.bitboards.occupied()) &
+            self.bitboards.colors[color as usize] != 0
+    }
+
+    /// Pieces of both colors attacking `square`, with sliders seeing through everything not in
+    /// `occupied`.
+    pub fn attackers_to(&self, square: usize, occupied: Bitboard) -> Bitboard {
+        let pieces = &self.bitboards.pieces;
+        let diagonal = pieces[PieceType::Bishop as usize] | pieces[PieceType::Queen as usize];
+        let straight = pieces[PieceType::Rook as usize] | pieces[PieceType::Queen as usize];
+        (PAWN_ATTACKS[1][square] & self.bitboards.of(0, PieceType::Pawn)) |
+            (PAWN_ATTACKS[0][square] & self.bitboards.of(1, PieceType::Pawn)) |
+            (KNIGHT_ATTACKS[square] & pieces[PieceType::Knight as usize]) |
+            (KING_ATTACKS[square] & pieces[PieceType::King as usize]) |
+            (bishop_attacks(square, occupied) & diagonal) |
+            (rook_attacks(square, occupied) & straight)
+    }
+
+    /// Whether playing `pmove` (with its start position filled) would leave the side to move
+    /// in check.
+    pub fn leaves_king_in_check(&self, pmove: PMove) -> bool {
+        let (start, end) = (pmove.start_position(), pmove.end_position());
+        let mut occupied = (self.bitboards.occupied() & !bit(start)) | bit(end);
+        let mut captured = bit(end);
+        if pmove.piece_type() == PieceType::Pawn && pmove.is_capture() && self[end].is_empty() {
+            captured = bit(end.with_y(start.y));
+            occupied &= !captured;
+        }
+        if
+            let Some((_, rook_pos)) =
+                CASTLING[(self.turn * 2 + pmove.castling() * 3) as usize]
+        {
+            let rook_start = (if pmove.castling() == 1 { 7 } else { 0 }, start.y).into();
+            occupied = (occupied & !bit(rook_start)) | bit(rook_pos.into());
+        }
+
+        let king = if pmove.piece_type() == PieceType::King {
+            end
+        } else {
+            self.king_positions[self.turn as usize]
+        };
+        self.attackers_to(square(king), occupied) &
+            self.bitboards.colors[(self.turn ^ 1) as usize] &
+            !captured != 0
     }
 
     pub fn in_bounds(pos: Position) -> bool {
@@ -183,33 +268,23 @@ impl Game {
             self.board
                 .iter()
                 .flat_map(|row| row.iter())
-                .all(|piece| (piece.is_empty() || piece.piece_type() == (PieceType::King as u8)))
+                .all(|piece| piece.is_empty() || piece.piece_type() == (PieceType::King as u8))
         {
             return true;
         }
 
         // Check if same position has been repeated 3 times
-        if
-            self.hash_history
-                .iter()
-                .filter(|pos| **pos == self.serialize_to_ints())
-                .count() >= 3
-        {
+        if self.repetition_count() >= 3 {
             return true;
         }
 
-        // Check if 50 moves have been made without a capture or pawn move
-        let last_50 = self.moves.last_50();
-        if
-            last_50.len() == 50 &&
-            last_50.iter().all(|x| x.piece_type() != PieceType::Pawn) &&
-            last_50.iter().all(|x| !x.is_capture())
-        {
+        // Check if 50 moves (100 plies) have been made without a capture or pawn move
+        if self.halfmove_clock >= 100 {
             return true;
         }
 
-        // TODO: Stalemate
-        if self.get_valid_moves().len() == 0 && !self.check_win() {
+        // No legal moves without being in check is stalemate
+        if self.get_valid_moves().is_empty() && !self.check_win() {
             return true;
         }
 
@@ -217,31 +292,6 @@ impl Game {
     }
 
     pub fn check_win(&self) -> bool {
-        self.get_valid_moves().len() == 0 && self.check[self.turn as usize]
-    }
-
-    pub fn get_valid_moves(&self) -> Vec<PMove> {
-        let mut moves = Vec::new();
-        for y in 0..8usize {
-            for x in 0..8 {
-                if self[(x, y)].is_empty() || self[(x, y)].color() != self.turn {
-                    continue;
-                }
-
-                let piece_moves = PMove::for_piece((x, y).into(), self[(x, y)], self);
-                moves.extend(piece_moves);
-            }
-        }
-        moves
-    }
-
-    pub fn serialize_to_ints(&self) -> [u64; 4] {
-        let mut ints = [0u64; 4];
-        for (i, row) in self.board.iter().enumerate() {
-            for (j, piece) in row.iter().enumerate() {
-                ints[i / 2] |= (piece.byte as u64) << (64 - ((j + 1) * 4 + ((i + 1) % 2) * 32));
-            }
-        }
-        ints
+        self.get_valid_moves().is_empty() && self.check[self.turn as usize]
     }
 }
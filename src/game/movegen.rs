@@ -0,0 +1,119 @@
+use crate::*;
+
+/// Which legal moves to generate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveKind {
+    All,
+    /// Captures and promotions, what quiescence search looks at.
+    Noisy,
+    /// Everything else, castling included.
+    Quiet,
+}
+
+impl PMove {
+    pub fn is_noisy(&self) -> bool {
+        self.is_capture() || self.promotion() != PieceType::None
+    }
+}
+
+impl Game {
+    pub fn get_valid_moves(&self) -> Vec<PMove> {
+        self.generate_moves(self.bitboards.colors[self.turn as usize], MoveKind::All)
+    }
+
+    /// Legal moves of `kind` for the side to move's pieces standing on `from`.
+    pub fn generate_moves(&self, from: Bitboard, kind: MoveKind) -> Vec<PMove> {
+        let us = self.turn as usize;
+        let own = self.bitboards.colors[us];
+        let enemy = self.bitboards.colors[us ^ 1];
+        let occupied = own | enemy;
+        let en_passant = self.en_passant.map_or(0, bit);
+        let allowed = match kind {
+            MoveKind::All => !own,
+            MoveKind::Noisy => enemy,
+            MoveKind::Quiet => !occupied,
+        };
+        let mut moves = Vec::new();
+
+        for start in squares(from & own) {
+            let piece_type = PieceType::from(self[position(start)].byte);
+            let targets = match piece_type {
+                // Pushes onto the last rank are noisy, so pawns are filtered move by move
+                PieceType::Pawn => self.pawn_targets(start, occupied, enemy | en_passant),
+                _ => attacks(piece_type, self.turn, start, occupied) & allowed,
+            };
+            let promotions = if
+                piece_type == PieceType::Pawn &&
+                position(start).y == (if us == 0 { 6 } else { 1 })
+            {
+                &PROMOTIONS[1..]
+            } else {
+                &PROMOTIONS[..1]
+            };
+
+            for end in squares(targets) {
+                let capture =
+                    (enemy & (1 << end)) != 0 ||
+                    (piece_type == PieceType::Pawn && (en_passant & (1 << end)) != 0);
+                for promotion in promotions.iter().rev() {
+                    let pmove = PMove::new(
+                        position(start).to_byte(),
+                        position(end).to_byte(),
+                        piece_type as u8,
+                        capture,
+                        0,
+                        *promotion as u8
+                    );
+                    if kind == MoveKind::All || (kind == MoveKind::Noisy) == pmove.is_noisy() {
+                        moves.push(pmove);
+                    }
+                }
+            }
+
+            if piece_type == PieceType::King && kind != MoveKind::Noisy {
+                for castling in [1, 2] {
+                    if let Some((king_pos, _)) = CASTLING[(self.turn * 2 + castling * 3) as usize] {
+                        if self.castling_allowed(castling) {
+                            moves.push(
+                                PMove::new(
+                                    position(start).to_byte(),
+                                    Position::from(king_pos).to_byte(),
+                                    PieceType::King as u8,
+                                    false,
+                                    castling,
+                                    0
+                                )
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        moves.retain(|pmove| !self.leaves_king_in_check(*pmove));
+        moves
+    }
+
+    /// Whether `pmove` is a legal move of `kind` in this position, for moves remembered from
+    /// other positions such as hash moves and killers.
+    pub fn is_legal(&self, pmove: PMove, kind: MoveKind) -> bool {
+        pmove.has_start_position() &&
+            self.generate_moves(bit(pmove.start_position()), kind).contains(&pmove)
+    }
+
+    /// Pushes onto empty squares, two steps from the home rank, and captures onto `capturable`.
+    fn pawn_targets(&self, start: usize, occupied: Bitboard, capturable: Bitboard) -> Bitboard {
+        let (single, home_rank) = if self.turn == 0 {
+            ((1u64 << start) << 8, RANK_1 << 8)
+        } else {
+            ((1u64 << start) >> 8, RANK_8 >> 8)
+        };
+        let single = single & !occupied;
+        let double = if (1u64 << start) & home_rank != 0 && single != 0 {
+            (if self.turn == 0 { single << 8 } else { single >> 8 }) & !occupied
+        } else {
+            0
+        };
+        single | double | (PAWN_ATTACKS[self.turn as usize][start] & capturable)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::*;
+
+/// A well-known test position with its published perft node counts, indexed by depth - 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    pub nodes: &'static [u64],
+}
+
+pub const PERFT_SUITE: [PerftPosition; 6] = [
+    PerftPosition {
+        name: "Initial position",
+        fen: START_FEN,
+        nodes: &[20, 400, 8902, 197281, 4865609],
+    },
+    PerftPosition {
+        name: "Kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        nodes: &[48, 2039, 97862, 4085603],
+    },
+    PerftPosition {
+        name: "Position 3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        nodes: &[14, 191, 2812, 43238, 674624],
+    },
+    PerftPosition {
+        name: "Position 4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        nodes: &[6, 264, 9467, 422333],
+    },
+    PerftPosition {
+        name: "Position 5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        nodes: &[44, 1486, 62379, 2103487],
+    },
+    PerftPosition {
+        name: "Position 6",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        nodes: &[46, 2079, 89890, 3894594],
+    },
+];
+
+impl PerftPosition {
+    /// Runs perft to `depth` and returns the node count, or `Err((expected, actual))` if it
+    /// disagrees with the published number.
+    pub fn verify(&self, depth: usize) -> Result<u64, (u64, u64)> {
+        let expected = self.nodes[depth - 1];
+        let actual = Game::from_fen(self.fen).expect("suite FENs are valid").perft(depth);
+        if actual == expected { Ok(actual) } else { Err((expected, actual)) }
+    }
+}
+
+impl Game {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep.
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.get_valid_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|pmove| self.perft_child(pmove, depth))
+            .sum()
+    }
+
+    /// Perft split by root move, for narrowing down which move a count goes wrong under.
+    pub fn divide(&mut self, depth: usize) -> Vec<(PMove, u64)> {
+        self.get_valid_moves()
+            .into_iter()
+            .map(|pmove| (pmove, self.perft_child(pmove, depth)))
+            .collect()
+    }
+
+    fn perft_child(&mut self, pmove: PMove, depth: usize) -> u64 {
+        self.make_pmove(pmove).expect("generated moves are legal");
+        let nodes = self.perft(depth.saturating_sub(1));
+        self.unmake_move();
+        nodes
+    }
+}
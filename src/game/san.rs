@@ -0,0 +1,82 @@
+use crate::*;
+
+impl Game {
+    /// Standard algebraic notation for `pmove` in the current position, including the
+    /// disambiguation needed to tell it apart from other legal moves and a `+`/`#` suffix.
+    /// `pmove` must be legal and have its start square filled in.
+    pub fn san(&self, pmove: PMove) -> String {
+        let legal_moves = self.get_valid_moves();
+        let mut san = self.san_without_suffix(pmove, &legal_moves);
+
+        let mut after = self.clone();
+        if after.make_pmove(pmove).is_ok() && after.check[after.turn as usize] {
+            san.push(if after.get_valid_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// SAN without the check suffix, disambiguated against `legal_moves`.
+    pub fn san_without_suffix(&self, pmove: PMove, legal_moves: &[PMove]) -> String {
+        match pmove.castling() {
+            1 => {
+                return "O-O".to_string();
+            }
+            2 => {
+                return "O-O-O".to_string();
+            }
+            _ => {}
+        }
+
+        let start = pmove.start_position();
+        let mut san = String::new();
+        if pmove.piece_type() == PieceType::Pawn {
+            if pmove.is_capture() {
+                san.push(start.to_string().chars().next().unwrap());
+            }
+        } else {
+            san.push(piece_letter(pmove.piece_type()));
+
+            let rivals = legal_moves
+                .iter()
+                .filter(|other| {
+                    other.piece_type() == pmove.piece_type() &&
+                        other.end_position() == pmove.end_position() &&
+                        other.start_position() != start
+                })
+                .map(|other| other.start_position())
+                .collect::<Vec<_>>();
+            let square = start.to_string();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|rival| rival.x != start.x) {
+                    san.push_str(&square[..1]);
+                } else if rivals.iter().all(|rival| rival.y != start.y) {
+                    san.push_str(&square[1..]);
+                } else {
+                    san.push_str(&square);
+                }
+            }
+        }
+
+        if pmove.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&pmove.end_position().to_string());
+        if pmove.promotion() != PieceType::None {
+            san.push('=');
+            san.push(piece_letter(pmove.promotion()));
+        }
+        san
+    }
+}
+
+pub fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::Pawn => 'P',
+        PieceType::Rook => 'R',
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Queen => 'Q',
+        PieceType::King => 'K',
+        PieceType::None => unreachable!(),
+    }
+}
@@ -0,0 +1,81 @@
+use crate::*;
+
+/// Piece values used by static exchange evaluation, indexed by `PieceType as usize`. The king's
+/// only needs to outweigh everything else.
+pub const SEE_VALUES: [i32; 7] = [0, 100, 500, 320, 330, 900, 20_000];
+
+/// Attackers are tried cheapest first.
+pub const SEE_ORDER: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+impl Game {
+    /// Static exchange evaluation: the material the side to move wins (or loses, if negative)
+    /// when `pmove` starts a sequence of captures on its end square, both sides recapturing with
+    /// their cheapest piece for as long as that pays. Sliders lined up behind a capturing piece
+    /// join in once it has moved. Pins are ignored. `pmove` must have its start square filled.
+    pub fn see(&self, pmove: PMove) -> i32 {
+        if pmove.castling() != 0 {
+            return 0;
+        }
+
+        let bitboards = &self.bitboards;
+        let (start, end) = (pmove.start_position(), pmove.end_position());
+        let target = square(end);
+        let mut occupied = bitboards.occupied() & !bit(start);
+        let mut gain = [0; 32];
+        gain[0] = SEE_VALUES[PieceType::from(self[end].byte) as usize];
+        if pmove.piece_type() == PieceType::Pawn && pmove.is_capture() && self[end].is_empty() {
+            gain[0] = SEE_VALUES[PieceType::Pawn as usize];
+            occupied &= !bit(end.with_y(start.y));
+        }
+        let mut on_square = SEE_VALUES[pmove.piece_type() as usize];
+        if pmove.promotion() != PieceType::None {
+            on_square = SEE_VALUES[pmove.promotion() as usize];
+            gain[0] += on_square - SEE_VALUES[PieceType::Pawn as usize];
+        }
+
+        let diagonal = bitboards.pieces[PieceType::Bishop as usize] |
+            bitboards.pieces[PieceType::Queen as usize];
+        let straight = bitboards.pieces[PieceType::Rook as usize] |
+            bitboards.pieces[PieceType::Queen as usize];
+        let mut attackers = self.attackers_to(target, occupied) & occupied;
+        let mut side = (self.turn ^ 1) as usize;
+        let mut depth = 0;
+        while depth + 1 < gain.len() {
+            let own = attackers & bitboards.colors[side];
+            let Some(piece_type) = SEE_ORDER.into_iter().find(
+                |piece_type| own & bitboards.pieces[*piece_type as usize] != 0
+            ) else {
+                break;
+            };
+            // The king may only recapture if nothing can take it back
+            if piece_type == PieceType::King && attackers & bitboards.colors[side ^ 1] != 0 {
+                break;
+            }
+
+            depth += 1;
+            gain[depth] = on_square - gain[depth - 1];
+
+            let from = own & bitboards.pieces[piece_type as usize];
+            occupied &= !(from & from.wrapping_neg());
+            attackers |=
+                (bishop_attacks(target, occupied) & diagonal) |
+                (rook_attacks(target, occupied) & straight);
+            attackers &= occupied;
+            on_square = SEE_VALUES[piece_type as usize];
+            side ^= 1;
+        }
+
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+}
@@ -0,0 +1,110 @@
+use crate::*;
+
+/// Everything `make_pmove` overwrites that can't be recomputed from the move itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoState {
+    pub captured: Piece,
+    pub castling_rights: CastlingRights,
+    pub en_passant: Option<Position>,
+    pub halfmove_clock: u32,
+    pub check: Checks,
+}
+
+impl Game {
+    pub(super) fn undo_state(&self, pmove: PMove) -> UndoState {
+        let end_piece = self[pmove.end_position()];
+        let captured = if !pmove.is_capture() {
+            Piece::empty(pmove.end_position())
+        } else if end_piece.is_empty() {
+            self[pmove.end_position().with_y(pmove.start_position().y)]
+        } else {
+            end_piece
+        };
+
+        UndoState {
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            check: self.check.clone(),
+        }
+    }
+
+    /// Takes back the last move, restoring the position exactly as it was before it was made.
+    /// Returns the move that was undone, or `None` if there is nothing to undo.
+    pub fn unmake_move(&mut self) -> Option<PMove> {
+        let undo = self.undo_history.pop()?;
+        let pmove = self.moves.pop()?;
+        self.hash_history.pop();
+        self.turn ^= 1;
+
+        let (start, end) = (pmove.start_position(), pmove.end_position());
+        if
+            let Some((king_pos, rook_pos)) =
+                CASTLING[(self.turn * 2 + pmove.castling() * 3) as usize]
+        {
+            let back_rank = (self.turn as usize) * 7;
+            let rook_start = if pmove.castling() == 1 { 7 } else { 0 };
+            let king = (self.turn << 3) + (PieceType::King as u8);
+            let rook = (self.turn << 3) + (PieceType::Rook as u8);
+            self.empty(king_pos);
+            self.empty(rook_pos);
+            self.put((4, back_rank).into(), king);
+            self.put((rook_start, back_rank).into(), rook);
+        } else {
+            self.empty(end);
+            self.put(start, (self.turn << 3) + pmove.piece_type_raw());
+            if !undo.captured.is_empty() {
+                self.put(undo.captured.position, undo.captured.byte);
+            }
+        }
+
+        if pmove.piece_type() == PieceType::King {
+            self.update_king_pos(start);
+        }
+        if self.turn == 1 {
+            self.fullmove_number -= 1;
+        }
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.check = undo.check;
+        self.hash = self.hash_history.last().copied().unwrap_or_else(|| self.zobrist());
+        Some(pmove)
+    }
+
+    /// Passes the turn without moving, for null-move pruning in search. Not allowed in check.
+    /// Must be taken back with `unmake_null_move` before any real move is undone.
+    pub fn make_null_move(&mut self) {
+        self.undo_history.push(UndoState {
+            captured: Piece::empty((0, 0).into()),
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            check: self.check.clone(),
+        });
+        let hash = self.hash ^ self.state_key();
+        self.en_passant = None;
+        // Positions from before the null move can't be repeated through it
+        self.halfmove_clock = 0;
+        self.turn ^= 1;
+        self.hash = hash ^ self.state_key();
+        self.hash_history.push(self.hash);
+    }
+
+    pub fn unmake_null_move(&mut self) {
+        let Some(undo) = self.undo_history.pop() else {
+            return;
+        };
+        self.hash_history.pop();
+        self.turn ^= 1;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = self.hash_history.last().copied().unwrap_or_else(|| self.zobrist());
+    }
+
+    /// Takes back up to `plies` moves, most recent first, and returns the moves undone.
+    pub fn takeback(&mut self, plies: usize) -> Vec<PMove> {
+        (0..plies).map_while(|_| self.unmake_move()).collect()
+    }
+}
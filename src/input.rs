@@ -1,110 +1,148 @@
-use crate::{ PieceType, Position, PMove };
+use crate::{ MoveError, PieceType, Position, PMove };
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Input {
     pub piece_type: PieceType,
     pub is_capture: bool,
+    pub start_file: Option<usize>,
+    pub start_rank: Option<usize>,
     pub end_position: Position,
     pub castling: u8,
     pub promotion: Option<PieceType>,
 }
 
 impl Input {
-    pub fn build(s: &str) -> Result<Input, String> {
-        if s.len() > 6 || s.len() < 2 {
-            return Err("Invalid input".to_string());
-        }
+    /// Parses a move in standard algebraic notation: `e4`, `exd5`, `Nbd7`, `R1e2`, `Qh4e1`,
+    /// `e8=Q`, `O-O`/`0-0` and so on. Check marks, annotation glyphs and `e.p.` are ignored.
+    /// Lowercase piece letters are also accepted, except that a lowercase `b` capturing on the
+    /// a- or c-file reads as a pawn capture, as it does in SAN.
+    pub fn build(s: &str) -> Result<Input, MoveError> {
+        let s = s.trim().trim_end_matches(['+', '#', '!', '?']);
+        let s = s.strip_suffix("e.p.").unwrap_or(s).trim_end();
 
-        if s == "O-O" || s == "O-O-O" {
+        let castling = match s {
+            "O-O" | "0-0" => 1,
+            "O-O-O" | "0-0-0" => 2,
+            _ => 0,
+        };
+        if castling != 0 {
             return Ok(Input {
                 piece_type: PieceType::King,
                 is_capture: false,
+                start_file: None,
+                start_rank: None,
                 end_position: (0usize, 0).into(),
-                castling: if s == "O-O" {
-                    1
-                } else {
-                    2
-                },
+                castling,
                 promotion: None,
             });
         }
 
-        let mut chars = s.chars();
-        println!("{}", s.len());
-        let piece_type = if s.len() == 2 {
-            PieceType::Pawn
-        } else {
-            match chars.next().unwrap() {
-                'p' => PieceType::Pawn,
-                'r' => PieceType::Rook,
-                'n' => PieceType::Knight,
-                'b' => PieceType::Bishop,
-                'q' => PieceType::Queen,
-                'k' => PieceType::King,
-                _ => {
-                    return Err("Invalid piece".to_string());
+        let mut chars = s.chars().collect::<Vec<_>>();
+        if chars.len() < 2 {
+            return Err(MoveError::Parse("Invalid input"));
+        }
+
+        // A trailing piece letter is a promotion after `=` or a move to the first or last rank
+        let promotion = match chars[..] {
+            [.., '=' | '1' | '8', letter] if letter.is_ascii_alphabetic() => {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
                 }
+                Some(match letter.to_ascii_lowercase() {
+                    'q' => PieceType::Queen,
+                    'r' => PieceType::Rook,
+                    'n' => PieceType::Knight,
+                    'b' => PieceType::Bishop,
+                    _ => {
+                        return Err(MoveError::Parse("Invalid promotion"));
+                    }
+                })
             }
-        };
-
-        let is_capture = if s.len() > 3 {
-            match chars.next().unwrap() {
-                'x' => true,
-                _ => false,
+            [.., letter] if letter.is_ascii_alphabetic() => {
+                return Err(MoveError::Parse("Invalid move"));
             }
-        } else {
-            false
+            _ => None,
         };
 
-        let x = match chars.next().unwrap() {
-            x @ 'a'..='h' => (x as usize) - ('a' as usize),
+        let y = match chars.pop() {
+            Some(y @ '1'..='8') => (y as usize) - ('1' as usize),
             _ => {
-                return Err("Invalid x coordinate".to_string());
+                return Err(MoveError::Parse("Invalid y coordinate"));
             }
         };
-        let y = match chars.next().unwrap() {
-            y @ '1'..='8' => (y as usize) - ('1' as usize),
+        let x = match chars.pop() {
+            Some(x @ 'a'..='h') => (x as usize) - ('a' as usize),
             _ => {
-                return Err("Invalid y coordinate".to_string());
+                return Err(MoveError::Parse("Invalid x coordinate"));
+            }
+        };
+
+        let is_capture = matches!(chars.last(), Some('x' | ':'));
+        if is_capture || chars.last() == Some(&'-') {
+            chars.pop();
+        }
+
+        let piece_type = match chars.first() {
+            Some('K' | 'k') => PieceType::King,
+            Some('Q' | 'q') => PieceType::Queen,
+            Some('R' | 'r') => PieceType::Rook,
+            Some('N' | 'n') => PieceType::Knight,
+            Some('B') => PieceType::Bishop,
+            Some('b') if !(chars.len() == 1 && is_capture && x.abs_diff(1) == 1) => {
+                PieceType::Bishop
             }
+            Some('P' | 'p') => PieceType::Pawn,
+            _ => PieceType::None,
         };
+        let hints = if piece_type == PieceType::None { &chars[..] } else { &chars[1..] };
+        let piece_type = if piece_type == PieceType::None { PieceType::Pawn } else { piece_type };
 
-        let promotion = if
-            (s.len() == 6 && is_capture) ||
-            (!is_capture && (s.len() == 5 || (s.len() == 4 && piece_type == PieceType::Pawn)))
-        {
-            chars.next().unwrap();
-            match chars.next().unwrap() {
-                'q' => Some(PieceType::Queen),
-                'r' => Some(PieceType::Rook),
-                'n' => Some(PieceType::Knight),
-                'b' => Some(PieceType::Bishop),
+        let (mut start_file, mut start_rank) = (None, None);
+        for hint in hints {
+            match hint {
+                file @ 'a'..='h' if start_file.is_none() && start_rank.is_none() => {
+                    start_file = Some((*file as usize) - ('a' as usize));
+                }
+                rank @ '1'..='8' if start_rank.is_none() => {
+                    start_rank = Some((*rank as usize) - ('1' as usize));
+                }
                 _ => {
-                    return Err("Invalid promotion".to_string());
+                    return Err(MoveError::Parse("Invalid input"));
                 }
             }
-        } else {
-            None
-        };
+        }
 
-        println!(
-            "{:b}",
-            PMove::partial(
-                0,
-                ((x << 3) | y) as u8,
-                piece_type as u8,
-                is_capture,
-                0,
-                promotion.map(|x| x as u8).unwrap_or(0)
-            ).0
-        );
+        // Only a pawn reaching the first or last rank promotes, and then it has to
+        if (piece_type == PieceType::Pawn && (y == 0 || y == 7)) != promotion.is_some() {
+            return Err(MoveError::Parse("Invalid promotion"));
+        }
 
         Ok(Input {
             piece_type,
             is_capture,
+            start_file,
+            start_rank,
             end_position: (x, y).into(),
             castling: 0,
             promotion,
         })
     }
 }
+
+impl From<Input> for PMove {
+    fn from(input: Input) -> Self {
+        if input.castling != 0 {
+            return PMove::castle(input.castling);
+        }
+
+        PMove::partial(
+            0,
+            input.end_position.to_byte(),
+            input.piece_type as u8,
+            input.is_capture,
+            0,
+            input.promotion.map(|x| x as u8).unwrap_or(0)
+        ).with_start_hint(input.start_file, input.start_rank)
+    }
+}
@@ -1,9 +1,27 @@
+pub use bitboard::*;
+pub use error::*;
 pub use eval::*;
-pub use game::{ checks::*, default::*, *, GameResult::* };
+pub use game::{
+    castling_rights::*,
+    checks::*,
+    default::*,
+    fen::*,
+    movegen::*,
+    perft::*,
+    san::*,
+    see::*,
+    undo::*,
+    *,
+    GameResult::*,
+};
 pub use input::*;
 pub use moves::*;
+pub use pgn::*;
 pub use piece::*;
 pub use position::*;
+pub use search::*;
+pub use uci::*;
+pub use xboard::*;
 pub use std::{
     array,
     convert::TryInto,
@@ -12,20 +30,53 @@ pub use std::{
     io::{ self, Write },
 };
 
+mod bitboard;
+mod error;
 mod eval;
 mod game;
 mod input;
 mod moves;
+mod pgn;
 mod piece;
 mod position;
+mod search;
+mod uci;
+mod xboard;
 #[cfg(test)]
 mod tests;
 
+/// Who makes the moves for one side in `play`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Player {
+    Human,
+    /// The engine, searching each move within these limits.
+    Computer(SearchLimits),
+}
+
+/// A game between two people at the same terminal.
 pub fn main_loop() -> Result<bool, Box<dyn Error>> {
+    play([Player::Human, Player::Human])
+}
+
+/// Plays a game in the terminal, `players[0]` with white and `players[1]` with black. Returns
+/// true when the game is over and false when the input ends first.
+pub fn play(players: [Player; 2]) -> Result<bool, Box<dyn Error>> {
     let mut game = Game::default();
+    let tt = TranspositionTable::default();
     println!("{}", game);
 
     loop {
+        if let Player::Computer(limits) = players[game.turn as usize] {
+            let (line, result) = computer_move(&mut game, limits, &tt);
+            println!("{}", game);
+            println!("{}", line);
+            if let Some(out) = game_over(&result) {
+                println!("{}", out);
+                return Ok(true);
+            }
+            continue;
+        }
+
         print!(
             "Enter move for {} (id: {}): ",
             if game.turn == 0 {
@@ -37,25 +88,100 @@ pub fn main_loop() -> Result<bool, Box<dyn Error>> {
         );
         io::stdout().flush()?;
         let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-
-        let out = match handle_input(&mut game, &mut input) {
-            Win(winner) => format!("{} wins", if winner == 0 { "white" } else { "black" }),
-            Draw => "Game drawn".to_string(),
-            InProgress(r) =>
-                match r {
-                    Ok(_) => "Move succesful".to_string(),
-                    Err(e) => {
-                        return Err(e);
-                    }
-                }
-        };
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(false);
+        }
+        // The full breakdown only on request, as it is too long to show after every move
+        if matches!(input.trim(), "eval" | "explain") {
+            println!("{}", Eval::explain(&game));
+            continue;
+        }
+
+        let played = game.moves.len();
+        let result = handle_input(&mut game, &mut input);
+        let undoing = matches!(result, InProgress(Ok(_))) && input.trim().starts_with("undo");
+        // Against the computer, taking back a move takes back its reply too
+        if undoing {
+            let mut undone = false;
+            while
+                matches!(players[game.turn as usize], Player::Computer(_)) &&
+                !game.takeback(1).is_empty()
+            {
+                undone = true;
+            }
+            if undone {
+                println!("{}", game);
+            }
+        }
+        if let Some(out) = game_over(&result) {
+            println!("{}", out);
+            return Ok(true);
+        }
+        match result {
+            InProgress(Err(e)) => println!("{}", e),
+            _ if undoing => {
+                let undone = played - game.moves.len();
+                println!("Took back {} move{}", undone, if undone == 1 { "" } else { "s" });
+            }
+            _ => println!("Move successful"),
+        }
+    }
+}
 
-        println!("{}", out);
+fn game_over(result: &GameResult) -> Option<String> {
+    match result {
+        Win(winner) => Some(format!("{} wins", if *winner == 0 { "white" } else { "black" })),
+        Draw => Some("Game drawn".to_string()),
+        InProgress(_) => None,
     }
 }
 
-fn handle_input(game: &mut Game, input: &mut String) -> GameResult {
+/// Where the game stands after a move.
+fn outcome(game: &Game) -> GameResult {
+    if game.check_win() {
+        Win(game.turn ^ 1)
+    } else if game.check_draw() {
+        Draw
+    } else {
+        InProgress(Ok(()))
+    }
+}
+
+/// Lets the engine move for the side to move. Returns the move with the engine's evaluation
+/// from white's point of view, and where the game stands after it.
+fn computer_move(
+    game: &mut Game,
+    limits: SearchLimits,
+    tt: &TranspositionTable
+) -> (String, GameResult) {
+    let result = search_with_table(game, limits, tt);
+    let Some(pmove) = result.best_move else {
+        return ("No legal moves".to_string(), outcome(game));
+    };
+
+    let side = if game.turn == 0 { "White" } else { "Black" };
+    let score = if game.turn == 0 { result.score } else { -result.score };
+    let score = match mate_in(score) {
+        Some(moves) => format!("#{}", moves),
+        None => format!("{:+.2}", (score as f64) / 100.0),
+    };
+    let line = format!(
+        "{} plays {} ({} at depth {}, {} nodes)",
+        side,
+        game.san(pmove),
+        score,
+        result.depth,
+        result.nodes
+    );
+    game.make_pmove(pmove).expect("searched moves are legal");
+    (line, outcome(game))
+}
+
+fn handle_input(game: &mut Game, input: &mut str) -> GameResult {
+    if let Some(plies) = input.trim().strip_prefix("undo") {
+        return handle_undo(game, plies.trim());
+    }
+
     let input = match input!(input.trim()) {
         Ok(input) => input,
         Err(e) => {
@@ -65,19 +191,29 @@ fn handle_input(game: &mut Game, input: &mut String) -> GameResult {
 
     match game.make_pmove(input) {
         Ok(_) => {
-            dbg!(&game.check);
             println!("{}", game);
-            println!("{}", Eval::from(&game));
-            if game.check_win() {
-                return Win(game.turn);
-            } else if game.check_draw() {
-                return Draw;
-            }
-        }
-        Err(e) => {
-            return InProgress(Err(e.into()));
+            println!("Evaluation: {}", Eval::from(&game).value);
+            outcome(game)
         }
+        Err(e) => InProgress(Err(e.into())),
     }
+}
+
+fn handle_undo(game: &mut Game, plies: &str) -> GameResult {
+    let plies = match plies {
+        "" => 1,
+        n =>
+            match n.parse() {
+                Ok(n) => n,
+                Err(_) => {
+                    return InProgress(Err("Invalid number of moves to undo".into()));
+                }
+            }
+    };
 
+    if game.takeback(plies).is_empty() {
+        return InProgress(Err("Nothing to undo".into()));
+    }
+    println!("{}", game);
     InProgress(Ok(()))
 }
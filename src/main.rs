@@ -1,9 +1,68 @@
+use std::env;
 use std::error::Error;
-use chessrs::main_loop;
+use std::io;
+use std::process;
+use std::time::Duration;
+use chessrs::{ main_loop, play, uci_loop, xboard_loop, Player, SearchLimits };
+
+const USAGE: &str =
+    "Usage: chessrs [uci | xboard | play white|black [STRENGTH] | watch [STRENGTH]]\n\
+     STRENGTH is one of --depth PLIES, --nodes NODES or --movetime MILLISECONDS";
+
+/// How long the computer thinks per move unless told otherwise.
+const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // TODO: Add command line arguments
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match args[..] {
+        // Runs the game
+        [] => {
+            main_loop()?;
+        }
+        // Speaks UCI to a chess GUI or match runner
+        ["uci"] => {
+            uci_loop(io::stdin().lock(), io::stdout())?;
+        }
+        // Speaks the Chess Engine Communication Protocol to xboard and older interfaces
+        ["xboard"] => {
+            xboard_loop(io::stdin().lock(), io::stdout())?;
+        }
+        // Against the computer, with the human playing `color`
+        ["play", color, ref strength @ ..] => {
+            let human = match color {
+                "white" => 0,
+                "black" => 1,
+                _ => usage(),
+            };
+            let mut players = [Player::Computer(parse_strength(strength)); 2];
+            players[human] = Player::Human;
+            play(players)?;
+        }
+        // The computer against itself
+        ["watch", ref strength @ ..] => {
+            play([Player::Computer(parse_strength(strength)); 2])?;
+        }
+        _ => usage(),
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
 
-    // Runs the game
-    main_loop().map(|_| ())
+fn parse_strength(args: &[&str]) -> SearchLimits {
+    let limits = match args {
+        [] => Some(SearchLimits::movetime(DEFAULT_MOVETIME)),
+        ["--depth", plies] => plies.parse().ok().map(SearchLimits::depth),
+        ["--nodes", nodes] => nodes.parse().ok().map(SearchLimits::nodes),
+        ["--movetime", millis] => {
+            millis.parse().ok().map(|millis| SearchLimits::movetime(Duration::from_millis(millis)))
+        }
+        _ => None,
+    };
+    limits.unwrap_or_else(|| usage())
 }
@@ -0,0 +1,490 @@
+use crate::*;
+use std::io::{ BufRead, Lines };
+
+pub const SEVEN_TAG_ROSTER: [&str; 7] = [
+    "Event",
+    "Site",
+    "Date",
+    "Round",
+    "White",
+    "Black",
+    "Result",
+];
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+const LINE_WIDTH: usize = 80;
+
+#[derive(Debug)]
+pub enum PgnError {
+    Io(io::Error),
+    InvalidTag(String),
+    UnterminatedComment,
+    UnterminatedVariation,
+    Fen(FenError),
+    IllegalMove {
+        ply: usize,
+        san: String,
+    },
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        match self {
+            PgnError::Io(e) => write!(f, "Could not read PGN: {}", e),
+            PgnError::InvalidTag(tag) => write!(f, "Invalid tag pair '{}'", tag),
+            PgnError::UnterminatedComment => write!(f, "Comment is missing its closing brace"),
+            PgnError::UnterminatedVariation =>
+                write!(f, "Variation is missing its closing parenthesis"),
+            PgnError::Fen(e) => write!(f, "Invalid FEN tag: {}", e),
+            PgnError::IllegalMove { ply, san } =>
+                write!(f, "Illegal move '{}' at ply {}", san, ply),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+impl From<io::Error> for PgnError {
+    fn from(e: io::Error) -> Self {
+        PgnError::Io(e)
+    }
+}
+
+impl From<FenError> for PgnError {
+    fn from(e: FenError) -> Self {
+        PgnError::Fen(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PgnMove {
+    pub san: String,
+    pub nags: Vec<u8>,
+    pub comment: Option<String>,
+}
+
+impl PgnMove {
+    pub fn new(san: &str) -> Self {
+        PgnMove {
+            san: san.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    /// Comment placed before the first move.
+    pub comment: Option<String>,
+    pub moves: Vec<PgnMove>,
+    pub result: String,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => {
+                *old = value.to_string();
+            }
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Parses a single game. Variations are skipped; comments and NAGs are kept on the move
+    /// they follow.
+    pub fn parse(text: &str) -> Result<PgnGame, PgnError> {
+        let mut game = PgnGame::default();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '[' => {
+                    let mut raw = String::new();
+                    let mut in_quotes = false;
+                    loop {
+                        match chars.next() {
+                            Some('\\') if in_quotes => {
+                                raw.push('\\');
+                                raw.extend(chars.next());
+                            }
+                            Some('"') => {
+                                in_quotes = !in_quotes;
+                                raw.push('"');
+                            }
+                            Some(']') if !in_quotes => {
+                                break;
+                            }
+                            Some(c) => raw.push(c),
+                            None => {
+                                return Err(PgnError::InvalidTag(raw));
+                            }
+                        }
+                    }
+                    let (name, value) = parse_tag(&raw)?;
+                    game.tags.push((name, value));
+                }
+                '{' => {
+                    let mut comment = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => {
+                                break;
+                            }
+                            Some(c) => comment.push(c),
+                            None => {
+                                return Err(PgnError::UnterminatedComment);
+                            }
+                        }
+                    }
+                    game.add_comment(comment.trim());
+                }
+                ';' => {
+                    let comment = chars
+                        .by_ref()
+                        .take_while(|c| *c != '\n')
+                        .collect::<String>();
+                    game.add_comment(comment.trim());
+                }
+                '(' => {
+                    let mut depth = 1;
+                    while depth > 0 {
+                        match chars.next() {
+                            Some('(') => {
+                                depth += 1;
+                            }
+                            Some(')') => {
+                                depth -= 1;
+                            }
+                            Some('{') => {
+                                if !chars.by_ref().any(|c| c == '}') {
+                                    return Err(PgnError::UnterminatedComment);
+                                }
+                            }
+                            Some(_) => {}
+                            None => {
+                                return Err(PgnError::UnterminatedVariation);
+                            }
+                        }
+                    }
+                }
+                '$' => {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                        digits.push(digit);
+                    }
+                    if let (Ok(nag), Some(last)) = (digits.parse(), game.moves.last_mut()) {
+                        last.nags.push(nag);
+                    }
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    let mut token = c.to_string();
+                    while
+                        let Some(c) = chars.next_if(
+                            |c| !c.is_whitespace() && !"[]{}();$".contains(*c)
+                        )
+                    {
+                        token.push(c);
+                    }
+                    game.add_token(&token);
+                }
+            }
+        }
+
+        if game.result.is_empty() {
+            game.result = game.tag("Result").unwrap_or("*").to_string();
+        }
+        Ok(game)
+    }
+
+    fn add_comment(&mut self, comment: &str) {
+        let target = match self.moves.last_mut() {
+            Some(last) => &mut last.comment,
+            None => &mut self.comment,
+        };
+        match target {
+            Some(existing) => {
+                existing.push(' ');
+                existing.push_str(comment);
+            }
+            None => {
+                *target = Some(comment.to_string());
+            }
+        }
+    }
+
+    fn add_token(&mut self, token: &str) {
+        if RESULTS.contains(&token) {
+            self.result = token.to_string();
+            return;
+        }
+
+        // Strip move numbers such as "12." or "12..." which may be glued to the move
+        let mut san = token;
+        let digits = san.len() - san.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 && san[digits..].starts_with('.') {
+            san = san[digits..].trim_start_matches('.');
+        }
+        if san.is_empty() {
+            return;
+        }
+
+        let glyphs = san.len() - san.trim_end_matches(['!', '?']).len();
+        let (san, glyph) = san.split_at(san.len() - glyphs);
+        let mut pgn_move = PgnMove::new(san);
+        if let Some(nag) = glyph_to_nag(glyph) {
+            pgn_move.nags.push(nag);
+        }
+        self.moves.push(pgn_move);
+    }
+
+    /// Replays the moves through `Game::make_pmove`, starting from the `FEN` tag if present.
+    pub fn to_game(&self) -> Result<Game, PgnError> {
+        let mut game = match self.tag("FEN") {
+            Some(fen) => Game::from_fen(fen)?,
+            None => Game::default(),
+        };
+
+        for (ply, pgn_move) in self.moves.iter().enumerate() {
+            let illegal = || PgnError::IllegalMove {
+                ply: ply + 1,
+                san: pgn_move.san.clone(),
+            };
+            let pmove = PMove::from_input(&pgn_move.san).map_err(|_| illegal())?;
+            game.make_pmove(pmove).map_err(|_| illegal())?;
+        }
+        Ok(game)
+    }
+
+    /// Builds a PGN record of `game` with the Seven Tag Roster, a `FEN` tag for games that
+    /// didn't start from the initial position, and the result worked out from the final position.
+    pub fn from_game(game: &Game) -> PgnGame {
+        let mut replay = match &game.start_fen {
+            Some(fen) => Game::from_fen(fen).expect("start_fen is always valid"),
+            None => Game::default(),
+        };
+
+        let moves = game.moves
+            .iter()
+            .map(|pmove| {
+                let san = replay.san(*pmove);
+                replay.make_pmove(*pmove).expect("recorded moves are legal");
+                PgnMove::new(&san)
+            })
+            .collect();
+
+        let result = if game.check_win() {
+            if game.turn == 0 { "0-1" } else { "1-0" }
+        } else if game.check_draw() {
+            "1/2-1/2"
+        } else {
+            "*"
+        };
+
+        let mut pgn = PgnGame {
+            tags: SEVEN_TAG_ROSTER.iter()
+                .map(|tag| {
+                    let unknown = if *tag == "Date" { "????.??.??" } else { "?" };
+                    (tag.to_string(), unknown.to_string())
+                })
+                .collect(),
+            comment: None,
+            moves,
+            result: result.to_string(),
+        };
+        pgn.set_tag("Result", result);
+        if let Some(fen) = &game.start_fen {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", fen);
+        }
+        pgn
+    }
+
+    fn movetext_tokens(&self) -> Vec<String> {
+        let (mut number, mut turn) = self
+            .tag("FEN")
+            .and_then(|fen| Game::from_fen(fen).ok())
+            .map_or((1, 0), |game| (game.fullmove_number, game.turn));
+
+        let mut tokens = Vec::new();
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        for (index, pgn_move) in self.moves.iter().enumerate() {
+            if turn == 0 {
+                tokens.push(format!("{}.", number));
+            } else if index == 0 || self.moves[index - 1].comment.is_some() {
+                tokens.push(format!("{}...", number));
+            }
+            tokens.push(pgn_move.san.clone());
+            tokens.extend(pgn_move.nags.iter().map(|nag| format!("${}", nag)));
+            if let Some(comment) = &pgn_move.comment {
+                tokens.push(format!("{{{}}}", comment));
+            }
+
+            if turn == 1 {
+                number += 1;
+            }
+            turn ^= 1;
+        }
+        tokens.push(self.result.clone());
+        tokens
+    }
+}
+
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmtResult {
+        for (name, value) in self.tags.iter() {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let mut line = String::new();
+        for token in self.movetext_tokens() {
+            if !line.is_empty() && line.len() + token.len() + 1 > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+/// Reads games one at a time from a multi-game PGN source.
+pub struct PgnReader<R> {
+    lines: Lines<R>,
+    pending: Option<String>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        PgnReader {
+            lines: reader.lines(),
+            pending: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut text = String::new();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            let line = match self.pending.take() {
+                Some(line) => line,
+                None =>
+                    match self.lines.next() {
+                        Some(Ok(line)) => line,
+                        Some(Err(e)) => {
+                            return Some(Err(e.into()));
+                        }
+                        None => {
+                            break;
+                        }
+                    }
+            };
+
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && in_movetext && !in_comment {
+                self.pending = Some(line);
+                break;
+            }
+            if trimmed.starts_with('%') && !in_comment {
+                continue;
+            }
+            text.push_str(&line);
+            text.push('\n');
+
+            if !trimmed.is_empty() && (in_movetext || !trimmed.starts_with('[')) {
+                in_movetext = true;
+                let (open, finished) = scan_movetext(trimmed, in_comment);
+                in_comment = open;
+                if finished {
+                    break;
+                }
+            }
+        }
+
+        if text.trim().is_empty() {
+            return None;
+        }
+        Some(PgnGame::parse(&text))
+    }
+}
+
+/// Reads a movetext `line`, given whether a `{` comment was open at its start. Returns whether
+/// one is still open at its end, and whether the line ends the game with a result.
+fn scan_movetext(line: &str, in_comment: bool) -> (bool, bool) {
+    let mut rest = line;
+    if in_comment {
+        match rest.find('}') {
+            Some(end) => {
+                rest = &rest[end + 1..];
+            }
+            None => {
+                return (true, false);
+            }
+        }
+    }
+
+    loop {
+        let end = rest.find(['{', ';']).unwrap_or(rest.len());
+        if rest[..end].split_whitespace().any(|token| RESULTS.contains(&token)) {
+            return (false, true);
+        }
+        // A `;` comment runs to the end of the line
+        if !rest[end..].starts_with('{') {
+            return (false, false);
+        }
+        match rest[end..].find('}') {
+            Some(close) => {
+                rest = &rest[end + close + 1..];
+            }
+            None => {
+                return (true, false);
+            }
+        }
+    }
+}
+
+fn parse_tag(raw: &str) -> Result<(String, String), PgnError> {
+    let invalid = || PgnError::InvalidTag(raw.to_string());
+    let (name, value) = raw.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    if name.is_empty() {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+fn glyph_to_nag(glyph: &str) -> Option<u8> {
+    match glyph {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
@@ -1,6 +1,8 @@
 use crate::*;
 
-pub const CASTLING: [Option<((usize, usize), (usize, usize))>; 9] = [
+type CastlingSquares = ((usize, usize), (usize, usize));
+
+pub const CASTLING: [Option<CastlingSquares>; 9] = [
     None,
     None,
     None,
@@ -20,10 +22,8 @@ pub const PROMOTIONS: [PieceType; 5] = [
     PieceType::Queen,
 ];
 
-pub enum PiecePath {
-    Straight,
-    Diagonal,
-}
+const START_FILE_KNOWN: u32 = 1 << 21;
+const START_RANK_KNOWN: u32 = 1 << 22;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PMove(pub u32);
@@ -47,126 +47,80 @@ impl PMove {
         )
     }
 
+    /// A move with its start square known, as produced by the move generator.
+    pub fn new(
+        start_position: u8,
+        end_position: u8,
+        piece_type: u8,
+        capture: bool,
+        castling: u8,
+        promotion: u8
+    ) -> Self {
+        Self(
+            PMove::partial(
+                start_position,
+                end_position,
+                piece_type,
+                capture,
+                castling,
+                promotion
+            ).0 |
+                START_FILE_KNOWN |
+                START_RANK_KNOWN
+        )
+    }
+
     pub fn for_piece(position: Position, piece: Piece, game: &Game) -> Vec<Self> {
-        match piece.piece_type().into() {
-            PieceType::Pawn => {
-                (
-                    if game.turn == 0 {
-                        POSSIBLE_WHITE_PAWN_MOVES.iter()
-                    } else {
-                        POSSIBLE_BLACK_PAWN_MOVES.iter()
-                    }
-                )
-                    .filter(|m| m.check(position, game))
-                    .map(|m|
-                        PMove::partial(
-                            position.to_byte(),
-                            (position + m.diff).to_byte(),
-                            piece.piece_type(),
-                            m.capture,
-                            0,
-                            if
-                                (position.x == 7 && game.turn == 1) ||
-                                (position.x == 1 && game.turn == 0)
-                            {
-                                PieceType::Queen as u8
-                            } else {
-                                0
-                            }
-                        )
-                    )
-                    .collect()
-            }
-            PieceType::Rook => {
-                POSSIBLE_ROOK_MOVES.iter()
-                    .filter(|m| m.check(position, game))
-                    .map(|m|
-                        PMove::partial(
-                            position.to_byte(),
-                            (position + m.diff).to_byte(),
-                            piece.piece_type(),
-                            m.capture,
-                            0,
-                            0
-                        )
-                    )
-                    .collect()
-            }
-            PieceType::Bishop => {
-                POSSIBLE_BISHOP_MOVES.iter()
-                    .filter(|m| m.check(position, game))
-                    .map(|m|
-                        PMove::partial(
-                            position.to_byte(),
-                            (position + m.diff).to_byte(),
-                            piece.piece_type(),
-                            m.capture,
-                            0,
-                            0
-                        )
-                    )
-                    .collect()
-            }
-            PieceType::Queen => {
-                POSSIBLE_BISHOP_MOVES.iter()
-                    .chain(POSSIBLE_ROOK_MOVES.iter())
-                    .filter(|m| m.check(position, game))
-                    .map(|m|
-                        PMove::partial(
-                            position.to_byte(),
-                            (position + m.diff).to_byte(),
-                            piece.piece_type(),
-                            m.capture,
-                            0,
-                            0
-                        )
-                    )
-                    .collect()
-            }
-            PieceType::Knight => {
-                POSSIBLE_KNIGHT_MOVES.iter()
-                    .filter(|m| m.check(position, game))
-                    .map(|m|
-                        PMove::partial(
-                            position.to_byte(),
-                            (position + m.diff).to_byte(),
-                            piece.piece_type(),
-                            m.capture,
-                            0,
-                            0
-                        )
-                    )
-                    .collect()
-            }
-            PieceType::King => {
-                POSSIBLE_KING_MOVES.iter()
-                    .filter(|m| m.check(position, game))
-                    .map(|m|
-                        PMove::partial(
-                            position.to_byte(),
-                            (position + m.diff).to_byte(),
-                            piece.piece_type(),
-                            m.capture,
-                            0,
-                            0
-                        )
-                    )
-                    .collect()
-            }
-            _ => Vec::new(),
+        if piece.is_empty() || piece.color() != game.turn {
+            return Vec::new();
         }
+        game.generate_moves(bit(position), MoveKind::All)
     }
 
     pub fn castle(castling: u8) -> Self {
         Self(((PieceType::King as u32) << 12) | ((castling as u32) << 16))
     }
 
+    /// Fills in the start square and marks it as known, so `fill_start_position` only accepts
+    /// that exact piece.
+    pub fn with_start_position(&self, start_position: Position) -> Self {
+        Self(
+            (self.0 & !(0b111111 << 6)) |
+                ((start_position.to_byte() as u32) << 6) |
+                START_FILE_KNOWN |
+                START_RANK_KNOWN
+        )
+    }
+
+    /// Records whichever part of the start square a SAN move spelled out, e.g. the `b` in `Nbd7`.
+    pub fn with_start_hint(&self, file: Option<usize>, rank: Option<usize>) -> Self {
+        let mut pmove = *self;
+        if let Some(file) = file {
+            pmove.0 = (pmove.0 & !(0b111 << 9)) | ((file as u32) << 9) | START_FILE_KNOWN;
+        }
+        if let Some(rank) = rank {
+            pmove.0 = (pmove.0 & !(0b111 << 6)) | ((rank as u32) << 6) | START_RANK_KNOWN;
+        }
+        pmove
+    }
+
+    pub fn has_start_position(&self) -> bool {
+        (self.0 & (START_FILE_KNOWN | START_RANK_KNOWN)) == (START_FILE_KNOWN | START_RANK_KNOWN)
+    }
+
+    /// Whether `start_position` agrees with whatever part of the start square is already known.
+    pub fn start_matches(&self, start_position: Position) -> bool {
+        let known = self.start_position();
+        ((self.0 & START_FILE_KNOWN) == 0 || known.x == start_position.x) &&
+            ((self.0 & START_RANK_KNOWN) == 0 || known.y == start_position.y)
+    }
+
     pub fn end_position(&self) -> Position {
         Position::from_byte((self.0 & 0b111111) as u8)
     }
 
     pub fn start_position(&self) -> Position {
-        Position::from_byte(((self.0 & (0b111111 << 6)) as u8) >> 6)
+        Position::from_byte(((self.0 & (0b111111 << 6)) >> 6) as u8)
     }
 
     pub fn piece_type_raw(&self) -> u8 {
@@ -196,192 +150,116 @@ impl PMove {
         (turn << 3) + self.piece_type_raw()
     }
 
-    pub fn fill_start_position(&mut self, game: &Game) -> bool {
-        println!("fill start position");
-        match &self.piece_type() {
-            PieceType::Pawn => {
-                let moves = (
-                    if game.turn == 0 {
-                        WHITE_PAWN_MOVES.iter()
-                    } else {
-                        BLACK_PAWN_MOVES.iter()
-                    }
-                )
-                    .filter(|m| { m.check(self, game) })
-                    .collect::<Vec<_>>();
-                match moves.len() {
-                    1 => {
-                        self.0 |= ((self.end_position() - moves[0].diff).to_byte() as u32) << 6;
-                    }
-                    _ => {
-                        return false;
-                    }
-                }
-            }
-            PieceType::Knight => {
-                let moves = KNIGHT_MOVES.iter()
-                    .filter(|m| { m.check(self, game) })
-                    .collect::<Vec<_>>();
-                match moves.len() {
-                    1 => {
-                        self.0 |= ((self.end_position() - moves[0].diff).to_byte() as u32) << 6;
-                    }
-                    _ => {
-                        return false;
-                    }
-                }
-            }
-            PieceType::Rook => {
-                let moves = ROOK_MOVES.iter()
-                    .filter(|m| { m.check(self, game) })
-                    .collect::<Vec<_>>();
-                match moves.len() {
-                    1 => {
-                        self.0 |= ((self.end_position() - moves[0].diff).to_byte() as u32) << 6;
-                    }
-                    _ => {
-                        return false;
-                    }
-                }
-            }
-            PieceType::Bishop => {
-                let moves = BISHOP_MOVES.iter()
-                    .filter(|m| { m.check(self, game) })
-                    .collect::<Vec<_>>();
-                match moves.len() {
-                    1 => {
-                        self.0 |= ((self.end_position() - moves[0].diff).to_byte() as u32) << 6;
-                    }
-                    _ => {
-                        return false;
-                    }
-                }
-            }
-            PieceType::Queen => {
-                let moves = BISHOP_MOVES.iter()
-                    .chain(ROOK_MOVES.iter())
-                    .filter(|m| { m.check(self, game) })
-                    .collect::<Vec<_>>();
-                match moves.len() {
-                    1 => {
-                        self.0 |= ((self.end_position() - moves[0].diff).to_byte() as u32) << 6;
-                    }
-                    _ => {
-                        return false;
-                    }
-                }
-            }
-            PieceType::King if self.castling() != 0 => {
-                if
-                    let Some((king_pos, rook_pos)) =
-                        CASTLING[(game.turn * 2 + self.castling() * 3) as usize]
-                {
-                    if game.castling_allowed(king_pos, rook_pos) {
-                        return false;
-                    }
-                    return true;
-                }
-                return false;
-            }
-            PieceType::King => {
-                let moves = KING_MOVES.iter()
-                    .filter(|m| { m.check(self, game) })
-                    .collect::<Vec<_>>();
-                match moves.len() {
-                    1 => {
-                        self.0 |= ((self.end_position() - moves[0].diff).to_byte() as u32) << 6;
-                    }
-                    _ => {
-                        return false;
-                    }
-                }
-            }
-            _ => todo!(),
+    /// Works out which piece makes this move and fills in its start square, so the move can be
+    /// played. Fails if no legal move of the side to move fits.
+    pub fn fill_start_position(&mut self, game: &Game) -> Result<(), MoveError> {
+        if self.piece_type() == PieceType::None {
+            return Err(MoveError::Parse("Invalid piece"));
         }
-        true
-    }
-
-    pub fn from_input(input: &str) -> Result<Self, String> {
-        if input.len() < 2 || input.len() > 6 {
-            return Err("Invalid input".to_string());
+        self.check_promotion(game.turn)?;
+        if self.castling() != 0 {
+            let Some((king_pos, _)) = CASTLING[(game.turn * 2 + self.castling() * 3) as usize]
+            else {
+                return Err(MoveError::Parse("Invalid castling"));
+            };
+            game.check_castling(self.castling())?;
+            let back_rank = (game.turn as usize) * 7;
+            *self = PMove::new(
+                Position::from((4usize, back_rank)).to_byte(),
+                Position::from(king_pos).to_byte(),
+                PieceType::King as u8,
+                false,
+                self.castling(),
+                0
+            );
+            return Ok(());
         }
 
-        if input == "O-O" {
-            return Ok(PMove::castle(1));
-        } else if input == "O-O-O" {
-            return Ok(PMove::castle(2));
-        }
+        let starts = squares(self.candidate_starts(game))
+            .map(position)
+            .filter(|start| self.start_matches(*start))
+            .collect::<Vec<_>>();
+        let legal = starts
+            .iter()
+            .filter(|start| !game.leaves_king_in_check(self.with_start_position(**start)))
+            .collect::<Vec<_>>();
 
-        let mut chars = input.chars();
-        let piece_type = if input.len() == 2 {
-            PieceType::Pawn
-        } else {
-            match chars.next().unwrap() {
-                'p' => PieceType::Pawn,
-                'r' => PieceType::Rook,
-                'n' => PieceType::Knight,
-                'b' => PieceType::Bishop,
-                'q' => PieceType::Queen,
-                'k' => PieceType::King,
-                _ => {
-                    return Err("Invalid piece".to_string());
-                }
+        match (starts.first(), legal.as_slice()) {
+            (_, [start]) => {
+                *self = self.with_start_position(**start);
+                Ok(())
             }
-        };
+            (None, _) => Err(MoveError::Unreachable(self.end_position())),
+            (Some(start), []) =>
+                Err(
+                    if self.piece_type() == PieceType::King || game.check[game.turn as usize] {
+                        MoveError::LeavesKingInCheck
+                    } else {
+                        MoveError::Pinned(*start)
+                    }
+                ),
+            _ => Err(MoveError::Ambiguous(self.end_position())),
+        }
+    }
 
-        let is_capture = if input.len() > 3 {
-            match chars.next().unwrap() {
-                'x' => true,
-                _ => false,
-            }
-        } else {
-            false
+    /// A pawn moving to the last rank of side `turn` has to promote to a queen, rook, bishop or
+    /// knight, and no other move may promote.
+    pub(crate) fn check_promotion(&self, turn: u8) -> Result<(), MoveError> {
+        let last_rank = ((turn ^ 1) as usize) * 7;
+        let promotes = self.piece_type() == PieceType::Pawn && self.end_position().y == last_rank;
+        let valid = match self.promotion() {
+            PieceType::Queen | PieceType::Rook | PieceType::Bishop | PieceType::Knight => promotes,
+            _ => !promotes,
         };
+        if valid { Ok(()) } else { Err(MoveError::Parse("Invalid promotion")) }
+    }
+
+    /// Squares holding a piece of the moving type and side that could make this move, if its
+    /// own king's safety is ignored. Found by looking backwards from the end square.
+    fn candidate_starts(&self, game: &Game) -> Bitboard {
+        let bitboards = game.bitboards();
+        let (us, them) = (game.turn, game.turn ^ 1);
+        let end = self.end_position();
+        let to = square(end);
+        let target = game[end];
+        let pieces = bitboards.of(us, self.piece_type());
 
-        let x = match chars.next().unwrap() {
-            x @ 'a'..='h' => (x as usize) - ('a' as usize),
-            _ => {
-                return Err("Invalid x coordinate".to_string());
+        if self.piece_type() == PieceType::Pawn {
+            if self.is_capture() {
+                let capturable =
+                    (!target.is_empty() && target.color() != us) ||
+                    (target.is_empty() && game.en_passant == Some(end));
+                return if capturable { PAWN_ATTACKS[them as usize][to] & pieces } else { 0 };
             }
-        };
-        let y = match chars.next().unwrap() {
-            y @ '1'..='8' => (y as usize) - ('1' as usize),
-            _ => {
-                return Err("Invalid y coordinate".to_string());
+            if !target.is_empty() {
+                return 0;
             }
-        };
+            let (single, home_rank) = if us == 0 {
+                (bit(end) >> 8, RANK_1 << 8)
+            } else {
+                (bit(end) << 8, RANK_8 >> 8)
+            };
+            let double = if single & bitboards.occupied() == 0 {
+                (if us == 0 { single >> 8 } else { single << 8 }) & home_rank
+            } else {
+                0
+            };
+            return (single | double) & pieces;
+        }
 
-        let promotion = if
-            (input.len() == 6 && is_capture) ||
-            (!is_capture &&
-                (input.len() == 5 || (input.len() == 4 && piece_type == PieceType::Pawn)))
-        {
-            chars.next().unwrap();
-            match chars.next().unwrap() {
-                'q' => Some(PieceType::Queen),
-                'r' => Some(PieceType::Rook),
-                'n' => Some(PieceType::Knight),
-                'b' => Some(PieceType::Bishop),
-                _ => {
-                    return Err("Invalid promotion".to_string());
-                }
-            }
+        let valid_target = if self.is_capture() {
+            !target.is_empty() && target.color() != us
         } else {
-            None
+            target.is_empty()
         };
+        if !valid_target {
+            return 0;
+        }
+        attacks(self.piece_type(), them, to, bitboards.occupied()) & pieces
+    }
 
-        let partial_move = PMove::partial(
-            0,
-            ((x << 3) | y) as u8,
-            piece_type as u8,
-            is_capture,
-            0,
-            promotion.map(|x| x as u8).unwrap_or(0)
-        );
-
-        println!("{:b}", partial_move.0);
-
-        Ok(partial_move)
+    pub fn from_input(input: &str) -> Result<Self, MoveError> {
+        Input::build(input).map(PMove::from)
     }
 }
 
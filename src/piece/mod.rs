@@ -1,12 +1,8 @@
 use crate::*;
 pub use moves::*;
 pub use bin_move::*;
-pub use move_lists::*;
-pub use direct_move_lists::*;
 
 mod moves;
-mod move_lists;
-mod direct_move_lists;
 mod bin_move;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd)]
@@ -73,22 +69,11 @@ impl Display for Piece {
 }
 
 impl Value for Piece {
-    fn value(&self) -> i32 {
-        match self.byte {
-            0b0001 => 1,
-            0b0010 => 5,
-            0b0011 => 3,
-            0b0100 => 3,
-            0b0101 => 9,
-            0b0110 => 1000,
-            0b1001 => -1,
-            0b1010 => -5,
-            0b1011 => -3,
-            0b1100 => -3,
-            0b1101 => -9,
-            0b1110 => -1000,
-            _ => 0,
-        }
+    /// Material plus the piece-square bonus for where the piece stands, negated for black.
+    fn value(&self) -> Score {
+        let piece_type = PieceType::from(self.byte);
+        let score = piece_type.value() + piece_square(piece_type, self.color(), self.position);
+        if self.color() == 0 { score } else { -score }
     }
 }
 
@@ -8,16 +8,16 @@ pub struct Position {
 
 impl Display for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let x = ((self.x as u8) + ('a' as u8)) as char;
-        let y = ((self.y as u8) + ('1' as u8)) as char;
+        let x = ((self.x as u8) + b'a') as char;
+        let y = ((self.y as u8) + b'1') as char;
 
         write!(f, "{}{}", x, y)
     }
 }
 
-impl Into<(isize, isize)> for Position {
-    fn into(self) -> (isize, isize) {
-        (self.x as isize, self.y as isize)
+impl From<Position> for (isize, isize) {
+    fn from(position: Position) -> Self {
+        (position.x as isize, position.y as isize)
     }
 }
 
@@ -113,8 +113,8 @@ impl Position {
     }
 
     pub fn from_byte(byte: u8) -> Self {
-        let x = (byte & 0b111000) as usize;
-        let y = (byte / 0b111) as usize;
+        let x = ((byte & 0b111000) >> 3) as usize;
+        let y = (byte & 0b111) as usize;
 
         Position { x, y }
     }
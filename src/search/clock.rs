@@ -0,0 +1,89 @@
+use crate::*;
+use std::time::{ Duration, Instant };
+
+/// Kept back from every budget for the time it takes to get the move to the GUI.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(20);
+/// Moves the remaining time is spread over when the time control doesn't say.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// The hard limit is at most this many times the soft one.
+const HARD_FACTOR: u32 = 4;
+/// Score drop between iterations, in centipawns, at which the soft limit is doubled.
+const SCORE_DROP: i32 = 100;
+
+/// Decides when a search is over. The soft limit is checked between iterations: no new one
+/// is started once it has passed. It is stretched while the best move keeps changing or the
+/// score drops. The hard limit stops the search wherever it is.
+#[derive(Debug, Clone)]
+pub(crate) struct TimeManager {
+    start: Instant,
+    pub(crate) soft: Option<Duration>,
+    pub(crate) hard: Option<Duration>,
+    /// Best move changes between iterations, halved every iteration so old ones count less.
+    instability: f64,
+    /// Best move and score of the last finished iteration.
+    previous: Option<(PMove, i32)>,
+}
+
+impl TimeManager {
+    /// Budgets the search of the side `turn` started at `start`. Without a clock or a move time
+    /// there are no limits, and neither is there for an infinite search.
+    pub(crate) fn new(limits: &SearchLimits, turn: u8, start: Instant) -> Self {
+        let clock = if turn == 0 {
+            limits.wtime.map(|time| (time, limits.winc))
+        } else {
+            limits.btime.map(|time| (time, limits.binc))
+        };
+        let (mut soft, mut hard) = match clock {
+            Some((time, increment)) => {
+                let (soft, hard) = budget(time, increment, limits.movestogo);
+                (Some(soft), Some(hard))
+            }
+            None => (None, None),
+        };
+        if let Some(movetime) = limits.movetime {
+            soft = Some(soft.map_or(movetime, |soft| soft.min(movetime)));
+            hard = Some(hard.map_or(movetime, |hard| hard.min(movetime)));
+        }
+        if limits.infinite {
+            (soft, hard) = (None, None);
+        }
+
+        TimeManager { start, soft, hard, instability: 0.0, previous: None }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Whether the search has to stop now, even in the middle of an iteration.
+    pub(crate) fn out_of_time(&self) -> bool {
+        self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+
+    /// Records the result of a finished iteration and returns whether to start another one.
+    pub(crate) fn next_iteration(&mut self, best_move: PMove, score: i32) -> bool {
+        self.instability /= 2.0;
+        let mut scale = 1.0;
+        if let Some((previous_move, previous_score)) = self.previous {
+            if previous_move != best_move {
+                self.instability += 1.0;
+            }
+            let drop = (previous_score - score).clamp(0, SCORE_DROP);
+            scale += (drop as f64) / (SCORE_DROP as f64);
+        }
+        scale *= 1.0 + self.instability / 2.0;
+        self.previous = Some((best_move, score));
+
+        self.soft.is_none_or(|soft| self.elapsed() < soft.mul_f64(scale))
+    }
+}
+
+/// Soft and hard limits for `time` left on the clock, spread over the moves until the next
+/// time control and topped up by most of the increment.
+fn budget(time: Duration, increment: Duration, movestogo: Option<u32>) -> (Duration, Duration) {
+    let available = time.saturating_sub(MOVE_OVERHEAD);
+    let moves = movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+    let soft = available / moves + increment * 3 / 4;
+    let hard = (soft * HARD_FACTOR).min(available * 4 / 5);
+    (soft.min(hard), hard)
+}
@@ -0,0 +1,720 @@
+use crate::*;
+pub(crate) use clock::*;
+pub(crate) use ordering::*;
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
+use std::thread;
+use std::time::{ Duration, Instant };
+pub use tt::*;
+
+mod clock;
+mod ordering;
+mod tt;
+
+/// Score of being checkmated at the root. A mate found `n` plies from the root scores
+/// `MATE - n` for the side giving it, so shorter mates score higher.
+pub const MATE: i32 = 30_000;
+/// Bound above any real score.
+pub const INFINITY: i32 = 32_000;
+/// Deepest iteration the search will start, and the ply limit for quiescence.
+pub const MAX_DEPTH: u32 = 64;
+
+/// Whether `score` reports a forced mate for either side.
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE - (MAX_DEPTH as i32) * 2
+}
+
+/// Full moves until mate for a mate score, negative when the side to move is getting mated.
+pub fn mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+    let plies = MATE - score.abs();
+    Some(if score > 0 { (plies + 1) / 2 } else { -plies / 2 })
+}
+
+/// When to stop searching. The search always finishes depth 1 so there is a move to play.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: u32,
+    pub nodes: Option<u64>,
+    /// Exactly this long, unless the search finishes earlier.
+    pub movetime: Option<Duration>,
+    /// Time left on white's and black's clocks. The side to move's is budgeted over the moves
+    /// left until the next time control.
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    /// Increments added to the clocks after each move.
+    pub winc: Duration,
+    pub binc: Duration,
+    /// Moves until the next time control, if it isn't sudden death.
+    pub movestogo: Option<u32>,
+    /// Ignore the clocks and search until told to stop or `depth` is reached.
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        SearchLimits { depth, ..SearchLimits::default() }
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        SearchLimits { nodes: Some(nodes), ..SearchLimits::default() }
+    }
+
+    pub fn movetime(movetime: Duration) -> Self {
+        SearchLimits { movetime: Some(movetime), ..SearchLimits::default() }
+    }
+
+    /// Sudden death with both clocks at `time` and an increment of `increment`.
+    pub fn clock(time: Duration, increment: Duration) -> Self {
+        SearchLimits {
+            wtime: Some(time),
+            btime: Some(time),
+            winc: increment,
+            binc: increment,
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn infinite() -> Self {
+        SearchLimits { infinite: true, ..SearchLimits::default() }
+    }
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        SearchLimits {
+            depth: MAX_DEPTH,
+            nodes: None,
+            movetime: None,
+            wtime: None,
+            btime: None,
+            winc: Duration::ZERO,
+            binc: Duration::ZERO,
+            movestogo: None,
+            infinite: false,
+        }
+    }
+}
+
+/// Selective search techniques, each of which can be switched off to measure what it is worth.
+/// All are on by default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Skip a move and prune if the position still fails high at reduced depth.
+    pub null_move: bool,
+    /// Search quiet moves late in the move order to reduced depth first.
+    pub late_move_reductions: bool,
+    /// Skip quiet moves near the leaves when the static evaluation is far below alpha.
+    pub futility: bool,
+    /// Prune near the leaves when the static evaluation is far above beta.
+    pub reverse_futility: bool,
+    /// Search one ply deeper when in check.
+    pub check_extensions: bool,
+    /// Start each iteration with a narrow window around the previous score.
+    pub aspiration_windows: bool,
+    /// Search threads sharing the transposition table (Lazy SMP). With one thread the search
+    /// is deterministic for a given table state and depth or node limit.
+    pub threads: usize,
+}
+
+impl SearchOptions {
+    /// Plain alpha-beta with none of the techniques.
+    pub fn none() -> Self {
+        SearchOptions {
+            null_move: false,
+            late_move_reductions: false,
+            futility: false,
+            reverse_futility: false,
+            check_extensions: false,
+            aspiration_windows: false,
+            threads: 1,
+        }
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            null_move: true,
+            late_move_reductions: true,
+            futility: true,
+            reverse_futility: true,
+            check_extensions: true,
+            aspiration_windows: true,
+            threads: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// `None` only when the side to move has no legal moves.
+    pub best_move: Option<PMove>,
+    /// Centipawns from the side to move's point of view, or a mate score.
+    pub score: i32,
+    pub pv: Vec<PMove>,
+    /// Nodes searched by all threads together.
+    pub nodes: u64,
+    /// Last fully searched depth.
+    pub depth: u32,
+    pub time: Duration,
+}
+
+impl SearchResult {
+    /// Nodes per second over the whole search.
+    pub fn nps(&self) -> u64 {
+        ((self.nodes as u128) * 1_000_000 / self.time.as_micros().max(1)) as u64
+    }
+}
+
+/// Lets another thread stop a running search, or tell it that the ponder move was played.
+#[derive(Debug, Default)]
+pub struct SearchControl {
+    stop: AtomicBool,
+    pondering: AtomicBool,
+}
+
+impl SearchControl {
+    /// Control for a search on the opponent's time. Time limits only apply after `ponderhit`,
+    /// and are counted from the start of the search.
+    pub fn pondering() -> Self {
+        SearchControl { stop: AtomicBool::new(false), pondering: AtomicBool::new(true) }
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn ponderhit(&self) {
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed)
+    }
+}
+
+/// Searches `game` with iterative deepening and returns the result of the deepest completed
+/// iteration. `game` is left as it was. Uses a fresh transposition table of the default size.
+pub fn search(game: &mut Game, limits: SearchLimits) -> SearchResult {
+    search_with_table(game, limits, &TranspositionTable::default())
+}
+
+/// Like `search`, but keeps what it learns in `tt` for later searches.
+pub fn search_with_table(
+    game: &mut Game,
+    limits: SearchLimits,
+    tt: &TranspositionTable
+) -> SearchResult {
+    search_with_options(game, limits, SearchOptions::default(), tt)
+}
+
+/// Like `search_with_table`, with some of the selective search techniques switched off.
+pub fn search_with_options(
+    game: &mut Game,
+    limits: SearchLimits,
+    options: SearchOptions,
+    tt: &TranspositionTable
+) -> SearchResult {
+    search_with_control(game, limits, options, tt, &SearchControl::default(), &mut |_| {})
+}
+
+/// Like `search_with_options`, stopping early when `control` says so. `report` is called with
+/// the result of each finished iteration, with the nodes and time of the search so far.
+pub fn search_with_control(
+    game: &mut Game,
+    limits: SearchLimits,
+    options: SearchOptions,
+    tt: &TranspositionTable,
+    control: &SearchControl,
+    report: &mut dyn FnMut(&SearchResult)
+) -> SearchResult {
+    tt.new_search();
+    let shared = Shared { stop: AtomicBool::new(false), nodes: AtomicU64::new(0), control };
+    let start = Instant::now();
+    let time = TimeManager::new(&limits, game.turn, start);
+    let helpers = options.threads.max(1) - 1;
+
+    let mut result = thread::scope(|scope| {
+        // Helpers search copies of the game and only share what they find through the table
+        let handles = (1..=helpers)
+            .map(|id| {
+                let (shared, mut game, time) = (&shared, game.clone(), time.clone());
+                scope.spawn(move || {
+                    let mut searcher = Searcher::new(limits, options, time, tt, shared, id);
+                    searcher.iterate(&mut game, &mut |_| {})
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut main = Searcher::new(limits, options, time, tt, &shared, 0);
+        let mut result = main.iterate(game, report);
+        shared.stop.store(true, Ordering::Relaxed);
+        for handle in handles {
+            let helper = handle.join().expect("search thread panicked");
+            result.nodes += helper.nodes;
+            // A helper that got further than the main thread has the better move
+            if helper.depth > result.depth && helper.best_move.is_some() {
+                result = SearchResult { nodes: result.nodes, ..helper };
+            }
+        }
+        result
+    });
+
+    result.time = start.elapsed();
+    result
+}
+
+/// State shared by all threads of one search.
+struct Shared<'a> {
+    stop: AtomicBool,
+    /// Nodes of all threads, flushed by each in batches of `NODE_BATCH`.
+    nodes: AtomicU64,
+    control: &'a SearchControl,
+}
+
+const NODE_BATCH: u64 = 1024;
+
+/// Deepest ply any line is searched to, extensions and quiescence included.
+const MAX_PLY: usize = (MAX_DEPTH as usize) * 2;
+/// Half width of the first aspiration window, in centipawns.
+const ASPIRATION_WINDOW: i32 = 25;
+/// Futility margins by remaining depth.
+const FUTILITY_MARGIN: [i32; 4] = [0, 150, 300, 450];
+/// Reverse futility margin per ply of remaining depth.
+const REVERSE_FUTILITY_MARGIN: i32 = 120;
+
+struct Searcher<'a> {
+    limits: SearchLimits,
+    options: SearchOptions,
+    time: TimeManager,
+    tt: &'a TranspositionTable,
+    shared: &'a Shared<'a>,
+    /// 0 for the main thread, which alone decides when the search is over.
+    id: usize,
+    pawn_table: PawnTable,
+    heuristics: Heuristics,
+    nodes: u64,
+    stopped: bool,
+    /// Whether an iteration has finished, so stopping leaves a move to play. Until then only
+    /// helper threads stop.
+    finished_iteration: bool,
+    /// Score of the best move so far in the current iteration, for when it is cut short.
+    root_score: i32,
+    /// Best move of the previous iteration, searched first at the root.
+    root_move: Option<PMove>,
+    /// Whether the move leading to each ply was a null move, so two never follow each other.
+    null_moves: [bool; MAX_PLY + 1],
+}
+
+impl<'a> Searcher<'a> {
+    fn new(
+        limits: SearchLimits,
+        options: SearchOptions,
+        time: TimeManager,
+        tt: &'a TranspositionTable,
+        shared: &'a Shared<'a>,
+        id: usize
+    ) -> Self {
+        Searcher {
+            limits,
+            options,
+            time,
+            tt,
+            shared,
+            id,
+            pawn_table: PawnTable::default(),
+            heuristics: Heuristics::new(),
+            nodes: 0,
+            stopped: false,
+            finished_iteration: false,
+            root_score: 0,
+            root_move: None,
+            null_moves: [false; MAX_PLY + 1],
+        }
+    }
+
+    /// Iterative deepening. Helper threads search every other iteration one ply deeper, so the
+    /// threads don't all work on the same tree at the same time. Only the main thread checks
+    /// the soft time limit.
+    fn iterate(
+        &mut self,
+        game: &mut Game,
+        report: &mut dyn FnMut(&SearchResult)
+    ) -> SearchResult {
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            pv: vec![],
+            nodes: 0,
+            depth: 0,
+            time: Duration::ZERO,
+        };
+
+        let max_depth = self.limits.depth.clamp(1, MAX_DEPTH);
+        for iteration in 1..=max_depth {
+            let depth = (iteration + ((self.id as u32) % 2)).min(max_depth);
+            let mut pv = vec![];
+            let score = self.aspiration(game, depth, result.score, &mut pv);
+            if self.stopped {
+                // Moves searched to the end before the stop beat the previous best move
+                if !pv.is_empty() {
+                    result.best_move = pv.first().copied();
+                    result.score = self.root_score;
+                    result.pv = pv;
+                }
+                break;
+            }
+
+            result = SearchResult {
+                best_move: pv.first().copied(),
+                score,
+                pv,
+                nodes: self.nodes,
+                depth,
+                time: Duration::ZERO,
+            };
+            self.root_move = result.best_move;
+            self.finished_iteration = true;
+            report(
+                &(SearchResult {
+                    nodes: self.shared.nodes.load(Ordering::Relaxed) + self.nodes % NODE_BATCH,
+                    time: self.time.elapsed(),
+                    ..result.clone()
+                })
+            );
+
+            // A full-width search this deep can't find a shorter mate than this one
+            if is_mate_score(score) && MATE - score.abs() <= (depth as i32) {
+                break;
+            }
+            // While pondering the time manager keeps track, but there is no time to run out of
+            if
+                self.id == 0 &&
+                result.best_move.is_some_and(|best| !self.time.next_iteration(best, score)) &&
+                !self.shared.control.is_pondering()
+            {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Searches the root with a window around the previous iteration's score, widening it on
+    /// whichever side the score falls outside until it fits.
+    fn aspiration(
+        &mut self,
+        game: &mut Game,
+        depth: u32,
+        previous: i32,
+        pv: &mut Vec<PMove>
+    ) -> i32 {
+        if !self.options.aspiration_windows || depth < 4 || is_mate_score(previous) {
+            return self.negamax(game, depth, 0, -INFINITY, INFINITY, pv);
+        }
+
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = (previous - delta, previous + delta);
+        loop {
+            let score = self.negamax(game, depth, 0, alpha, beta, pv);
+            if self.stopped {
+                return score;
+            }
+            if score <= alpha {
+                alpha = (score - delta).max(-INFINITY);
+            } else if score >= beta {
+                beta = (score + delta).min(INFINITY);
+            } else {
+                return score;
+            }
+            delta *= 2;
+        }
+    }
+
+    fn check_limits(&mut self) {
+        if self.nodes.is_multiple_of(NODE_BATCH) {
+            self.shared.nodes.fetch_add(NODE_BATCH, Ordering::Relaxed);
+            if self.time.out_of_time() && !self.shared.control.is_pondering() {
+                self.shared.stop.store(true, Ordering::Relaxed);
+            }
+        }
+        let nodes = self.shared.nodes.load(Ordering::Relaxed) + self.nodes % NODE_BATCH;
+        if self.limits.nodes.is_some_and(|limit| nodes >= limit) {
+            self.shared.stop.store(true, Ordering::Relaxed);
+        }
+        if
+            (self.shared.stop.load(Ordering::Relaxed) || self.shared.control.is_stopped()) &&
+            (self.finished_iteration || self.id != 0)
+        {
+            self.stopped = true;
+        }
+    }
+
+    fn negamax(
+        &mut self,
+        game: &mut Game,
+        depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        mut beta: i32,
+        pv: &mut Vec<PMove>
+    ) -> i32 {
+        pv.clear();
+        if ply > 0 {
+            if game.halfmove_clock >= 100 || game.repetition_count() >= 2 {
+                return 0;
+            }
+
+            // Mate distance pruning: no line from here beats a mate already found nearer the root
+            alpha = alpha.max(-MATE + (ply as i32));
+            beta = beta.min(MATE - (ply as i32) - 1);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+        let in_check = game.check[game.turn as usize];
+        let depth = if self.options.check_extensions && in_check { depth + 1 } else { depth };
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+
+        self.nodes += 1;
+        self.check_limits();
+        if self.stopped {
+            return 0;
+        }
+
+        let entry = self.tt.probe(game.hash, ply);
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+            if cutoff {
+                return entry.score;
+            }
+        }
+        let hash_move = entry.and_then(|entry| entry.best_move);
+
+        let pv_node = beta - alpha > 1;
+        let prunable = ply > 0 && !pv_node && !in_check;
+        let static_eval = if in_check {
+            -INFINITY
+        } else {
+            evaluate_with(game, &mut self.pawn_table)
+        };
+
+        if
+            self.options.reverse_futility &&
+            prunable &&
+            depth <= 3 &&
+            !is_mate_score(beta) &&
+            static_eval - REVERSE_FUTILITY_MARGIN * (depth as i32) >= beta
+        {
+            return static_eval;
+        }
+
+        // Zugzwang guard: with only pawns left, passing can be better than any move
+        if
+            self.options.null_move &&
+            prunable &&
+            depth >= 3 &&
+            static_eval >= beta &&
+            !self.null_moves[ply] &&
+            has_pieces(game)
+        {
+            let reduction = 2 + depth / 4;
+            game.make_null_move();
+            self.null_moves[ply + 1] = true;
+            let mut child_pv = vec![];
+            let score = -self.negamax(
+                game,
+                depth.saturating_sub(1 + reduction),
+                ply + 1,
+                -beta,
+                -beta + 1,
+                &mut child_pv
+            );
+            self.null_moves[ply + 1] = false;
+            game.unmake_null_move();
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return if is_mate_score(score) { beta } else { score };
+            }
+        }
+
+        let futile =
+            self.options.futility &&
+            prunable &&
+            (depth as usize) < FUTILITY_MARGIN.len() &&
+            static_eval + FUTILITY_MARGIN[depth as usize] <= alpha;
+
+        let first = if ply == 0 { self.root_move.or(hash_move) } else { hash_move };
+        let mut picker = MovePicker::new(game, first, &self.heuristics, ply);
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = vec![];
+        let mut quiets_tried = vec![];
+        let mut searched = 0;
+        while let Some(pmove) = picker.next(game, &self.heuristics) {
+            game.make_pmove(pmove).expect("generated moves are legal");
+            let gives_check = game.check[game.turn as usize];
+            let quiet = !pmove.is_noisy() && !gives_check;
+            if futile && quiet && searched > 0 {
+                game.unmake_move();
+                continue;
+            }
+            if !pmove.is_noisy() {
+                quiets_tried.push(pmove);
+            }
+
+            let reduction = if
+                self.options.late_move_reductions &&
+                depth >= 3 &&
+                searched >= 3 &&
+                quiet &&
+                !in_check
+            {
+                late_move_reduction(depth, searched, pv_node)
+            } else {
+                0
+            };
+            let mut score = -INFINITY;
+            if reduction > 0 {
+                score = -self.negamax(
+                    game,
+                    depth - 1 - reduction,
+                    ply + 1,
+                    -alpha - 1,
+                    -alpha,
+                    &mut child_pv
+                );
+            }
+            if reduction == 0 || score > alpha {
+                score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            }
+            game.unmake_move();
+            searched += 1;
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                if score > alpha {
+                    alpha = score;
+                    best_move = Some(pmove);
+                    if ply == 0 {
+                        self.root_score = score;
+                    }
+                    pv.clear();
+                    pv.push(pmove);
+                    pv.extend_from_slice(&child_pv);
+                    if alpha >= beta {
+                        if !pmove.is_noisy() {
+                            self.heuristics.update(game, ply, depth, pmove, &quiets_tried);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        if best == -INFINITY {
+            return if in_check { -MATE + (ply as i32) } else { 0 };
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt.store(game.hash, ply, best_move, depth, bound, best);
+        best
+    }
+
+    /// Searches captures and promotions until the position is quiet, so the static evaluation
+    /// is never taken in the middle of an exchange. When in check every evasion is tried.
+    fn quiescence(&mut self, game: &mut Game, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        self.check_limits();
+        if self.stopped {
+            return 0;
+        }
+
+        if ply >= MAX_PLY {
+            return evaluate_with(game, &mut self.pawn_table);
+        }
+        let in_check = game.check[game.turn as usize];
+        // Evasions are searched like the main search, so they stop at a draw like it does
+        if in_check && (game.halfmove_clock >= 100 || game.repetition_count() >= 2) {
+            return 0;
+        }
+
+        let mut best = -INFINITY;
+        if !in_check {
+            best = evaluate_with(game, &mut self.pawn_table);
+            if best >= beta {
+                return best;
+            }
+            alpha = alpha.max(best);
+        }
+
+        // When in check every evasion is searched, so running out of moves means mate
+        let mut picker = if in_check {
+            MovePicker::new(game, None, &self.heuristics, ply)
+        } else {
+            MovePicker::noisy()
+        };
+        while let Some(pmove) = picker.next(game, &self.heuristics) {
+            game.make_pmove(pmove).expect("generated moves are legal");
+            let score = -self.quiescence(game, ply + 1, -beta, -alpha);
+            game.unmake_move();
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                if score > alpha {
+                    alpha = score;
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+        if in_check && best == -INFINITY {
+            return -MATE + (ply as i32);
+        }
+        best
+    }
+}
+
+/// Whether the side to move has anything besides pawns and the king.
+fn has_pieces(game: &Game) -> bool {
+    let bitboards = game.bitboards();
+    let pawns_and_king = bitboards.pieces[PieceType::Pawn as usize] |
+        bitboards.pieces[PieceType::King as usize];
+    bitboards.colors[game.turn as usize] & !pawns_and_king != 0
+}
+
+/// Plies to reduce the `searched`-th move (counting from 0) at `depth`: more for later moves
+/// and deeper searches, less on the principal variation. Always leaves at least one ply.
+fn late_move_reduction(depth: u32, searched: usize, pv_node: bool) -> u32 {
+    let reduction = ((depth as f64).ln() * (searched as f64).ln() / 2.0) as u32;
+    let reduction = if pv_node { reduction.saturating_sub(1) } else { reduction };
+    reduction.min(depth - 2)
+}
@@ -0,0 +1,250 @@
+use crate::*;
+
+/// History scores stay within this, so old successes fade as new ones are recorded.
+const MAX_HISTORY: i32 = 16_384;
+
+/// What the search has learned about quiet moves so far: killers that caused a cutoff at the
+/// same ply, the reply that refuted each previous move, and how often each from-to pair of
+/// either side caused a cutoff (the butterfly history).
+pub(crate) struct Heuristics {
+    killers: Vec<[Option<PMove>; 2]>,
+    /// Indexed by the side to move, then the piece type and end square of the previous move.
+    countermoves: Box<[[[Option<PMove>; 64]; 7]; 2]>,
+    /// Indexed by the side to move, then start and end square.
+    history: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl Heuristics {
+    pub(crate) fn new() -> Self {
+        Heuristics {
+            killers: vec![[None; 2]; (MAX_DEPTH as usize) * 2],
+            countermoves: Box::new([[[None; 64]; 7]; 2]),
+            history: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    fn killers(&self, ply: usize) -> [Option<PMove>; 2] {
+        self.killers.get(ply).copied().unwrap_or_default()
+    }
+
+    fn countermove(&self, game: &Game) -> Option<PMove> {
+        let previous = game.moves.last()?;
+        self.countermoves[game.turn as usize][previous.piece_type() as usize]
+            [square(previous.end_position())]
+    }
+
+    fn history(&self, game: &Game, pmove: PMove) -> i32 {
+        self.history[game.turn as usize][square(pmove.start_position())]
+            [square(pmove.end_position())]
+    }
+
+    /// Records that the quiet move `best` caused a cutoff at `ply` after the quiet moves in
+    /// `tried` failed to.
+    pub(crate) fn update(
+        &mut self,
+        game: &Game,
+        ply: usize,
+        depth: u32,
+        best: PMove,
+        tried: &[PMove]
+    ) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != Some(best) {
+                killers[1] = killers[0];
+                killers[0] = Some(best);
+            }
+        }
+        if let Some(previous) = game.moves.last() {
+            self.countermoves[game.turn as usize][previous.piece_type() as usize][
+                square(previous.end_position())
+            ] = Some(best);
+        }
+
+        let bonus = ((depth * depth) as i32).min(MAX_HISTORY);
+        for pmove in tried {
+            let bonus = if *pmove == best { bonus } else { -bonus };
+            let entry = &mut self.history[game.turn as usize][square(pmove.start_position())][
+                square(pmove.end_position())
+            ];
+            *entry += bonus - (*entry * bonus.abs()) / MAX_HISTORY;
+        }
+    }
+}
+
+/// Most valuable victim, least valuable attacker: captures of big pieces by small ones first.
+/// Promotions count the piece gained as part of the victim.
+pub(crate) fn mvv_lva(game: &Game, pmove: PMove) -> i32 {
+    let victim = if pmove.is_capture() && game[pmove.end_position()].is_empty() {
+        PieceType::Pawn
+    } else {
+        PieceType::from(game[pmove.end_position()].byte)
+    };
+    let promotion = SEE_VALUES[pmove.promotion() as usize];
+    (SEE_VALUES[victim as usize] + promotion) * 8 -
+        SEE_ORDER.iter()
+            .position(|piece_type| *piece_type == pmove.piece_type())
+            .unwrap_or(0) as i32
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+    HashMove,
+    GenerateNoisy,
+    GoodNoisy,
+    Killers,
+    Countermove,
+    GenerateQuiets,
+    Quiets,
+    BadNoisy,
+    Done,
+}
+
+/// Hands out the moves of a position best first, generating them in stages: the hash move
+/// before any generation, captures and promotions that don't lose material by MVV-LVA, killers
+/// and the countermove, quiet moves by history, and losing captures last. A cutoff early on
+/// saves generating and sorting the rest.
+pub(crate) struct MovePicker {
+    stage: Stage,
+    hash_move: Option<PMove>,
+    killers: [Option<PMove>; 2],
+    killer_index: usize,
+    countermove: Option<PMove>,
+    /// Only captures and promotions that don't lose material, for quiescence search.
+    noisy_only: bool,
+    moves: Vec<(PMove, i32)>,
+    bad_noisy: Vec<PMove>,
+}
+
+impl MovePicker {
+    pub(crate) fn new(
+        game: &Game,
+        hash_move: Option<PMove>,
+        heuristics: &Heuristics,
+        ply: usize
+    ) -> Self {
+        MovePicker {
+            stage: Stage::HashMove,
+            hash_move: hash_move.filter(|pmove| game.is_legal(*pmove, MoveKind::All)),
+            killers: heuristics.killers(ply),
+            killer_index: 0,
+            countermove: heuristics.countermove(game),
+            noisy_only: false,
+            moves: vec![],
+            bad_noisy: vec![],
+        }
+    }
+
+    pub(crate) fn noisy() -> Self {
+        MovePicker {
+            stage: Stage::GenerateNoisy,
+            hash_move: None,
+            killers: [None; 2],
+            killer_index: 0,
+            countermove: None,
+            noisy_only: true,
+            moves: vec![],
+            bad_noisy: vec![],
+        }
+    }
+
+    /// Whether `pmove` was already handed out before the stage that generated it.
+    fn already_picked(&self, pmove: PMove) -> bool {
+        Some(pmove) == self.hash_move ||
+            (!pmove.is_noisy() &&
+                (self.killers.contains(&Some(pmove)) || Some(pmove) == self.countermove))
+    }
+
+    /// Removes and returns the highest scored move left, a selection sort step.
+    fn pick_best(&mut self) -> Option<PMove> {
+        let index = (0..self.moves.len()).max_by_key(|index| self.moves[*index].1)?;
+        Some(self.moves.swap_remove(index).0)
+    }
+
+    pub(crate) fn next(&mut self, game: &Game, heuristics: &Heuristics) -> Option<PMove> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::GenerateNoisy;
+                    if self.hash_move.is_some() {
+                        return self.hash_move;
+                    }
+                }
+                Stage::GenerateNoisy => {
+                    let own = game.bitboards().colors[game.turn as usize];
+                    self.moves = game
+                        .generate_moves(own, MoveKind::Noisy)
+                        .into_iter()
+                        .filter(|pmove| Some(*pmove) != self.hash_move)
+                        .map(|pmove| (pmove, mvv_lva(game, pmove)))
+                        .collect();
+                    self.stage = Stage::GoodNoisy;
+                }
+                Stage::GoodNoisy => {
+                    while let Some(pmove) = self.pick_best() {
+                        if game.see(pmove) >= 0 {
+                            return Some(pmove);
+                        }
+                        if !self.noisy_only {
+                            self.bad_noisy.push(pmove);
+                        }
+                    }
+                    self.stage = if self.noisy_only { Stage::Done } else { Stage::Killers };
+                }
+                Stage::Killers => {
+                    while self.killer_index < self.killers.len() {
+                        let killer = self.killers[self.killer_index];
+                        self.killer_index += 1;
+                        if
+                            let Some(killer) = killer.filter(|killer| {
+                                Some(*killer) != self.hash_move &&
+                                    game.is_legal(*killer, MoveKind::Quiet)
+                            })
+                        {
+                            return Some(killer);
+                        }
+                    }
+                    self.stage = Stage::Countermove;
+                }
+                Stage::Countermove => {
+                    self.stage = Stage::GenerateQuiets;
+                    let countermove = self.countermove.filter(|pmove| {
+                        Some(*pmove) != self.hash_move &&
+                            !self.killers.contains(&Some(*pmove)) &&
+                            game.is_legal(*pmove, MoveKind::Quiet)
+                    });
+                    // Forget a countermove that wasn't played, so Quiets doesn't skip it
+                    self.countermove = countermove;
+                    if countermove.is_some() {
+                        return countermove;
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    let own = game.bitboards().colors[game.turn as usize];
+                    self.moves = game
+                        .generate_moves(own, MoveKind::Quiet)
+                        .into_iter()
+                        .filter(|pmove| !self.already_picked(*pmove))
+                        .map(|pmove| (pmove, heuristics.history(game, pmove)))
+                        .collect();
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => {
+                    if let Some(pmove) = self.pick_best() {
+                        return Some(pmove);
+                    }
+                    self.bad_noisy.reverse();
+                    self.stage = Stage::BadNoisy;
+                }
+                Stage::BadNoisy => {
+                    if let Some(pmove) = self.bad_noisy.pop() {
+                        return Some(pmove);
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => {
+                    return None;
+                }
+            }
+        }
+    }
+}
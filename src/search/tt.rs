@@ -0,0 +1,183 @@
+use crate::*;
+use std::sync::atomic::{ AtomicU64, AtomicU8, Ordering };
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// How the stored score relates to the true score of the position.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact.
+    Exact,
+    /// The search failed high: the true score is at least this.
+    Lower,
+    /// The search failed low: the true score is at most this.
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TtEntry {
+    pub best_move: Option<PMove>,
+    pub depth: u32,
+    pub bound: Bound,
+    /// Score relative to the probed node, mate scores already adjusted back to its ply.
+    pub score: i32,
+}
+
+/// One slot holds the key XORed with the data next to the data itself, so a slot torn by two
+/// threads writing at once fails the key check instead of returning mixed-up data.
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Fixed-size hash table of search results, keyed on `Game::hash`. Entries from a deeper search
+/// are kept over shallower ones, unless they are left over from an earlier search. All methods
+/// take `&self`, so the table can be shared between search threads.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// A table using about `megabytes` MB of memory, with room for at least one entry.
+    pub fn new(megabytes: usize) -> Self {
+        let len = (megabytes * 1024 * 1024 / std::mem::size_of::<Slot>()).max(1);
+        TranspositionTable {
+            slots: (0..len).map(|_| Slot::default()).collect(),
+            generation: AtomicU8::new(1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.generation.store(1, Ordering::Relaxed);
+    }
+
+    /// Marks the start of a new search, so entries stored before it can be replaced freely.
+    pub fn new_search(&self) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation.store((generation % 63) + 1, Ordering::Relaxed);
+    }
+
+    fn slot(&self, hash: u64) -> &Slot {
+        &self.slots[(((hash as u128) * (self.slots.len() as u128)) >> 64) as usize]
+    }
+
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<TtEntry> {
+        let slot = self.slot(hash);
+        let data = slot.data.load(Ordering::Relaxed);
+        if data == 0 || slot.key.load(Ordering::Relaxed) ^ data != hash {
+            return None;
+        }
+
+        let (best_move, score, depth, bound, _) = unpack(data);
+        Some(TtEntry { best_move, depth, bound, score: score_from_tt(score, ply) })
+    }
+
+    pub fn store(
+        &self,
+        hash: u64,
+        ply: usize,
+        best_move: Option<PMove>,
+        depth: u32,
+        bound: Bound,
+        score: i32
+    ) {
+        let slot = self.slot(hash);
+        let generation = self.generation.load(Ordering::Relaxed);
+        let old = slot.data.load(Ordering::Relaxed);
+        let mut best_move = best_move;
+        if old != 0 {
+            let (old_move, _, old_depth, _, old_generation) = unpack(old);
+            let same_position = slot.key.load(Ordering::Relaxed) ^ old == hash;
+            if old_generation == generation && !same_position && depth < old_depth {
+                return;
+            }
+            // Don't lose the best move of this position to a search that failed low
+            if same_position {
+                best_move = best_move.or(old_move);
+            }
+        }
+
+        let data = pack(best_move, score_to_tt(score, ply), depth, bound, generation);
+        slot.key.store(hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    /// Permille of sampled slots holding an entry from the current search, as UCI `hashfull`.
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let sample = self.slots.len().min(1000);
+        let used = self.slots[..sample]
+            .iter()
+            .filter(|slot| {
+                let data = slot.data.load(Ordering::Relaxed);
+                data != 0 && unpack(data).4 == generation
+            })
+            .count();
+        ((used * 1000) / sample) as u32
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+/// Mate scores are stored as distance from the stored node rather than from the root, so the
+/// entry stays right when the position is reached at another ply.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if is_mate_score(score) {
+        score + score.signum() * (ply as i32)
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if is_mate_score(score) {
+        score - score.signum() * (ply as i32)
+    } else {
+        score
+    }
+}
+
+// Bits 0-31 move, 32-47 score, 48-55 depth, 56-57 bound, 58-63 generation. The generation is
+// never zero, so an empty slot is all zeroes.
+fn pack(best_move: Option<PMove>, score: i32, depth: u32, bound: Bound, generation: u8) -> u64 {
+    let bound = match bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    (best_move.map_or(0, |pmove| pmove.0) as u64) |
+        ((score as i16 as u16 as u64) << 32) |
+        ((depth.min(255) as u64) << 48) |
+        ((bound as u64) << 56) |
+        ((generation as u64) << 58)
+}
+
+fn unpack(data: u64) -> (Option<PMove>, i32, u32, Bound, u8) {
+    let best_move = Some(PMove(data as u32)).filter(|pmove| pmove.0 != 0);
+    let score = (data >> 32) as u16 as i16 as i32;
+    let depth = ((data >> 48) & 0xff) as u32;
+    let bound = match (data >> 56) & 0b11 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+    (best_move, score, depth, bound, (data >> 58) as u8)
+}
@@ -0,0 +1,63 @@
+use super::*;
+
+fn sq(name: &str) -> usize {
+    square(parse_square(name).unwrap())
+}
+
+fn set(names: &[&str]) -> Bitboard {
+    names.iter().fold(0, |bitboard, name| bitboard | (1 << sq(name)))
+}
+
+#[test]
+fn leaper_attacks() {
+    assert_eq!(KNIGHT_ATTACKS[sq("a1")], set(&["b3", "c2"]));
+    assert_eq!(KNIGHT_ATTACKS[sq("d4")].count_ones(), 8);
+    assert_eq!(KING_ATTACKS[sq("h8")], set(&["g8", "g7", "h7"]));
+    assert_eq!(PAWN_ATTACKS[0][sq("a2")], set(&["b3"]));
+    assert_eq!(PAWN_ATTACKS[1][sq("e5")], set(&["d4", "f4"]));
+}
+
+#[test]
+fn slider_attacks_stop_at_blockers() {
+    let occupied = set(&["d6", "b4", "d2", "f4"]);
+    assert_eq!(
+        rook_attacks(sq("d4"), occupied),
+        set(&["d5", "d6", "c4", "b4", "e4", "f4", "d3", "d2"])
+    );
+    assert_eq!(rook_attacks(sq("a1"), 0).count_ones(), 14);
+
+    let occupied = set(&["b2", "f6"]);
+    assert_eq!(bishop_attacks(sq("d4"), occupied), set(&[
+        "c3", "b2", "e5", "f6", "c5", "b6", "a7", "e3", "f2", "g1",
+    ]));
+    assert_eq!(queen_attacks(sq("d4"), 0).count_ones(), 27);
+}
+
+#[test]
+fn bitboards_follow_the_board() -> Result<(), FenError> {
+    for position in PERFT_SUITE.iter() {
+        let mut game = Game::from_fen(position.fen)?;
+        for pmove in game.get_valid_moves() {
+            assert!(game.make_pmove(pmove).is_ok());
+            assert_eq!(
+                *game.bitboards(),
+                Bitboards::from_board(game.get_board()),
+                "{} {}",
+                position.name,
+                pmove.to_uci()
+            );
+            game.unmake_move();
+            assert_eq!(*game.bitboards(), Bitboards::from_board(game.get_board()));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn attackers_to_square() -> Result<(), FenError> {
+    let game = Game::from_fen("4k3/1B6/8/3p4/8/4N3/8/3RK3 w - - 0 1")?;
+    let occupied = game.bitboards().occupied();
+    assert_eq!(game.attackers_to(sq("d5"), occupied), set(&["b7", "e3", "d1"]));
+    assert_eq!(game.attackers_to(sq("c4"), occupied), set(&["d5", "e3"]));
+    Ok(())
+}
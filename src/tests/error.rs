@@ -0,0 +1,67 @@
+use super::*;
+
+fn play(fen: &str, san: &str) -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen(fen)?;
+    game.make_pmove(input!(san)?)?;
+    Ok(())
+}
+
+fn move_error(fen: &str, san: &str) -> MoveError {
+    let mut game = Game::from_fen(fen).unwrap();
+    match input!(san) {
+        Ok(pmove) => game.make_pmove(pmove).unwrap_err(),
+        Err(error) => error,
+    }
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(move_error(START_FEN, "Zf3"), MoveError::Parse("Invalid input"));
+    assert_eq!(move_error(START_FEN, "e9"), MoveError::Parse("Invalid y coordinate"));
+}
+
+#[test]
+fn unreachable_and_ambiguous() {
+    assert_eq!(move_error(START_FEN, "e5"), MoveError::Unreachable((4usize, 4).into()));
+    assert_eq!(move_error(START_FEN, "Qh5"), MoveError::Unreachable((7usize, 4).into()));
+
+    let rooks = "4k3/8/8/8/8/8/4K3/R6R w - - 0 1";
+    assert_eq!(move_error(rooks, "Rf1"), MoveError::Ambiguous((5usize, 0).into()));
+    assert!(play(rooks, "Rhf1").is_ok());
+}
+
+#[test]
+fn pinned_piece_and_king_safety() {
+    let pinned = "4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1";
+    assert_eq!(move_error(pinned, "Nc3"), MoveError::Pinned((4usize, 1).into()));
+    assert_eq!(move_error(pinned, "Ke2"), MoveError::Unreachable((4usize, 1).into()));
+
+    let attacked = "3r2k1/8/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(move_error(attacked, "Kd2"), MoveError::LeavesKingInCheck);
+
+    let in_check = "4r1k1/8/8/8/8/8/3N4/4K3 w - - 0 1";
+    assert_eq!(move_error(in_check, "Nb3"), MoveError::LeavesKingInCheck);
+    assert!(play(in_check, "Ne4").is_ok());
+}
+
+#[test]
+fn castling_errors() {
+    assert_eq!(
+        move_error("r3k2r/8/8/8/8/8/5r2/R3K2R w KQ - 0 1", "O-O"),
+        MoveError::CastlingThroughCheck
+    );
+    assert_eq!(
+        move_error("r3k2r/8/8/8/8/8/8/R3K2R w Q - 0 1", "O-O"),
+        MoveError::CastlingNotAllowed
+    );
+    assert_eq!(
+        move_error("r3k2r/8/8/8/8/8/8/RN2K2R w KQ - 0 1", "O-O-O"),
+        MoveError::CastlingNotAllowed
+    );
+}
+
+#[test]
+fn game_over() {
+    assert_eq!(move_error("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", "Kh8"), MoveError::GameOver);
+    assert_eq!(move_error("7k/5Q2/8/8/8/8/8/6K1 b - - 0 1", "Kg8"), MoveError::GameOver);
+}
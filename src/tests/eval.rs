@@ -0,0 +1,225 @@
+use super::*;
+
+/// The same position with colors swapped and the board flipped, so the side to move should get
+/// the same score.
+fn mirror(fen: &str) -> String {
+    let fields = fen.split_whitespace().collect::<Vec<_>>();
+    let swap_case = |c: char| {
+        if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() }
+    };
+    let placement = fields[0]
+        .split('/')
+        .rev()
+        .map(|rank| rank.chars().map(swap_case).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("/");
+    let turn = if fields[1] == "w" { "b" } else { "w" };
+    let castling = if fields[2] == "-" {
+        "-".to_string()
+    } else {
+        let mut castling = fields[2].chars().map(swap_case).collect::<Vec<_>>();
+        castling.sort_by_key(|c| (c.is_ascii_lowercase(), *c == 'q' || *c == 'Q'));
+        castling.into_iter().collect()
+    };
+    let en_passant = match fields[3] {
+        "-" => "-".to_string(),
+        square => format!("{}{}", &square[..1], if &square[1..] == "3" { 6 } else { 3 }),
+    };
+    format!("{} {} {} {} {} {}", placement, turn, castling, en_passant, fields[4], fields[5])
+}
+
+#[test]
+fn start_position_is_level() {
+    let game = Game::default();
+    assert_eq!(game_phase(&game), MAX_PHASE);
+    assert_eq!(evaluate(&game), TEMPO.taper(MAX_PHASE));
+}
+
+#[test]
+fn score_is_from_side_to_move() -> Result<(), FenError> {
+    for position in PERFT_SUITE.iter() {
+        let game = Game::from_fen(position.fen)?;
+        let mirrored = Game::from_fen(&mirror(position.fen))?;
+        assert_eq!(evaluate(&game), evaluate(&mirrored), "{}", position.name);
+    }
+
+    let white = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1")?;
+    let black = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1")?;
+    assert!(evaluate(&white) > 800);
+    assert!(evaluate(&black) < -800);
+    assert_eq!(Eval::explain(&white).total() - TEMPO, Eval::explain(&black).total() + TEMPO);
+    Ok(())
+}
+
+#[test]
+fn phase_follows_material() -> Result<(), FenError> {
+    assert_eq!(game_phase(&Game::from_fen("4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1")?), 0);
+    assert_eq!(game_phase(&Game::from_fen("3qk3/8/8/8/8/8/8/R3K3 w - - 0 1")?), 6);
+    Ok(())
+}
+
+#[test]
+fn taper_blends_middlegame_and_endgame() {
+    let score = Score::new(100, -20);
+    assert_eq!(score.taper(MAX_PHASE), 100);
+    assert_eq!(score.taper(0), -20);
+    assert_eq!(score.taper(MAX_PHASE / 2), 40);
+}
+
+#[test]
+fn pieces_prefer_good_squares() {
+    let knight = |square| Piece::new(PieceType::Knight as u8, square).value_at(MAX_PHASE);
+    assert!(knight((4, 4)) > knight((0, 0)));
+
+    // The king hides in the middlegame and walks to the centre in the endgame
+    let king = |square, phase| Piece::new(PieceType::King as u8, square).value_at(phase);
+    assert!(king((6, 0), MAX_PHASE) > king((4, 3), MAX_PHASE));
+    assert!(king((6, 0), 0) < king((4, 3), 0));
+
+    let black_pawn = Piece::new(0b1000 | (PieceType::Pawn as u8), (3, 1));
+    assert!(black_pawn.value_at(0) < -PieceType::Pawn.value().eg);
+}
+
+#[test]
+fn explain_adds_up_to_the_score() -> Result<(), FenError> {
+    for position in PERFT_SUITE.iter() {
+        let game = Game::from_fen(position.fen)?;
+        let eval = Eval::explain(&game);
+        let total = eval
+            .terms()
+            .iter()
+            .map(|(_, term)| term.white - term.black)
+            .sum::<Score>();
+        let white_view = if game.turn == 0 { eval.value } else { -eval.value };
+        assert_eq!(total.taper(eval.phase), white_view, "{}", position.name);
+        assert_eq!(eval.value, evaluate(&game));
+    }
+    Ok(())
+}
+
+#[test]
+fn explain_table() -> Result<(), FenError> {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1")?;
+    let eval = Eval::explain(&game);
+    assert_eq!(eval.material.white, PieceType::Queen.value());
+    assert_eq!(eval.material.black, Score::default());
+    assert_eq!(eval.tempo.black, TEMPO);
+
+    let table = eval.to_string();
+    for name in ["Material", "Piece-square", "Pawn structure", "Mobility", "King safety", "Tempo"] {
+        assert!(table.contains(name), "{}", table);
+    }
+    assert!(table.ends_with(&format!("Evaluation: {} (black to move)\n", eval.value)));
+    Ok(())
+}
+
+fn pawns(fen: &str) -> PawnEntry {
+    PawnEntry::new(&Game::from_fen(fen).unwrap())
+}
+
+#[test]
+fn passed_pawns() {
+    let d5 = bit(parse_square("d5").unwrap());
+    assert_eq!(pawns("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1").passed[0], d5);
+    assert_eq!(pawns("4k3/8/3p4/3P4/8/8/8/4K3 w - - 0 1").passed[0], 0);
+    assert_eq!(pawns("4k3/4p3/8/3P4/8/8/8/4K3 w - - 0 1").passed[0], 0);
+    // Only the front pawn of a doubled pair counts
+    assert_eq!(pawns("4k3/8/8/3P4/3P4/8/8/4K3 w - - 0 1").passed[0], d5);
+
+    let free = Game::from_fen("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+    let blocked = Game::from_fen("3nk3/8/8/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+    let term = |game: &Game| PawnEntry::new(game).term(game).white;
+    assert!(term(&free).eg > term(&blocked).eg);
+    assert_eq!(PawnEntry::new(&free).scores, PawnEntry::new(&blocked).scores);
+}
+
+#[test]
+fn pawn_weaknesses() {
+    let white = |fen| pawns(fen).scores[0];
+    let connected = white("4k3/8/8/8/8/8/2PP4/4K3 w - - 0 1");
+    let isolated = white("4k3/8/8/8/8/8/2P1P3/4K3 w - - 0 1");
+    let doubled = white("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1");
+    assert!(connected.mg > isolated.mg && connected.eg > isolated.eg);
+    assert!(isolated.eg > doubled.eg);
+
+    let backward = white("4k3/8/8/4p3/2P5/3P4/8/4K3 w - - 0 1");
+    let not_backward = white("4k3/8/4p3/8/2P5/3P4/8/4K3 w - - 0 1");
+    assert!(backward.mg < not_backward.mg);
+
+    let candidate = white("4k3/8/2p5/8/3P4/2P5/8/4K3 w - - 0 1");
+    let no_candidate = white("4k3/8/2p1p3/8/3P4/2P5/8/4K3 w - - 0 1");
+    assert!(candidate.eg > no_candidate.eg);
+}
+
+#[test]
+fn pawn_key_only_sees_pawns() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let key = game.pawn_key();
+    game.make_pmove(input!("Nf3")?)?;
+    assert_eq!(game.pawn_key(), key);
+    game.make_pmove(input!("e5")?)?;
+    assert_ne!(game.pawn_key(), key);
+    assert_eq!(Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1")?.pawn_key(), 0);
+
+    let mut table = PawnTable::new(16);
+    for position in PERFT_SUITE.iter() {
+        let game = Game::from_fen(position.fen)?;
+        assert_eq!(table.probe(&game), PawnEntry::new(&game));
+        assert_eq!(table.probe(&game), PawnEntry::new(&game));
+        assert_eq!(Eval::explain_with(&game, &mut table), Eval::explain(&game));
+    }
+    Ok(())
+}
+
+#[test]
+fn mobility_counts_moves() -> Result<(), FenError> {
+    let open = Eval::explain(&Game::from_fen("4k3/8/8/8/3B4/8/8/4K3 w - - 0 1")?);
+    let cornered = Eval::explain(&Game::from_fen("4k3/8/8/8/8/8/1P6/B3K3 w - - 0 1")?);
+    let bishop = Weights::DEFAULT.mobility[PieceType::Bishop as usize];
+    assert_eq!(open.mobility.white, Score::new(bishop.mg * 13, bishop.eg * 13));
+    assert_eq!(cornered.mobility.white, Score::default());
+    Ok(())
+}
+
+#[test]
+fn king_safety_terms() -> Result<(), FenError> {
+    let sheltered = Eval::explain(&Game::from_fen("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1")?);
+    let exposed = Eval::explain(&Game::from_fen("4k3/8/8/8/5PPP/8/8/6K1 w - - 0 1")?);
+    assert!(sheltered.king_safety.white.mg > exposed.king_safety.white.mg);
+
+    let open = Eval::explain(&Game::from_fen("4k3/8/8/8/8/8/5P1P/6K1 w - - 0 1")?);
+    let half_open = Eval::explain(&Game::from_fen("4k3/6p1/8/8/8/8/5P1P/6K1 w - - 0 1")?);
+    assert_eq!(
+        sheltered.king_safety.white - open.king_safety.white,
+        Weights::DEFAULT.shield_close - Weights::DEFAULT.open_file
+    );
+    assert_eq!(
+        half_open.king_safety.white - open.king_safety.white,
+        Weights::DEFAULT.half_open_file - Weights::DEFAULT.open_file
+    );
+
+    // One attacker counts for nothing, a second brings the first into play
+    let queen = Eval::explain(&Game::from_fen("4k3/8/8/8/8/7q/5PPP/6K1 w - - 0 1")?);
+    let queen_and_knight = Eval::explain(&Game::from_fen("4k3/8/8/8/4n3/7q/5PPP/6K1 w - - 0 1")?);
+    assert_eq!(queen.king_safety.white, sheltered.king_safety.white);
+    assert!(queen_and_knight.king_safety.white.mg < sheltered.king_safety.white.mg);
+    Ok(())
+}
+
+#[test]
+fn weights_can_be_changed() -> Result<(), FenError> {
+    let game = Game::from_fen(PERFT_SUITE[1].fen)?;
+    let weights = Weights {
+        mobility: [Score::default(); 7],
+        shield_close: Score::default(),
+        shield_far: Score::default(),
+        open_file: Score::default(),
+        half_open_file: Score::default(),
+        ..Weights::default()
+    };
+    let eval = Eval::explain_weighted(&game, &weights);
+    assert_eq!(eval.mobility, Term::default());
+    assert_eq!(eval.material, Eval::explain(&game).material);
+    assert_eq!(Eval::explain_weighted(&game, &Weights::default()), Eval::explain(&game));
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+#[test]
+fn start_position_matches_default() {
+    assert_eq!(Game::from_fen(START_FEN), Ok(Game::default()));
+    assert_eq!(Game::default().to_fen(), START_FEN);
+}
+
+#[test]
+fn round_trip() -> Result<(), FenError> {
+    for fen in [
+        KIWIPETE,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w Kq c6 0 2",
+        "4k3/8/8/8/8/8/8/4K2R b K - 12 40",
+    ] {
+        assert_eq!(Game::from_fen(fen)?.to_fen(), fen);
+    }
+    Ok(())
+}
+
+#[test]
+fn fills_game_state() -> Result<(), FenError> {
+    let game = Game::from_fen("rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w Kq c6 3 2")?;
+
+    assert_eq!(game.turn, 0);
+    assert_eq!(game.king_positions, [(4usize, 0).into(), (4usize, 7).into()]);
+    assert_eq!(game.castling_rights, CastlingRights(WHITE_KINGSIDE | BLACK_QUEENSIDE));
+    assert_eq!(game.en_passant, Some((2usize, 5).into()));
+    assert_eq!(game.halfmove_clock, 3);
+    assert_eq!(game.fullmove_number, 2);
+    assert_eq!(game[(2usize, 4)], Piece::new(0b1001, (2, 4)));
+    Ok(())
+}
+
+#[test]
+fn optional_move_counters() -> Result<(), FenError> {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - -")?;
+
+    assert_eq!(game.halfmove_clock, 0);
+    assert_eq!(game.fullmove_number, 1);
+    Ok(())
+}
+
+#[test]
+fn sets_check_from_position() -> Result<(), FenError> {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1")?;
+
+    assert!(game.check[0u8]);
+    assert!(!game.check[1u8]);
+    Ok(())
+}
+
+#[test]
+fn typed_errors() {
+    assert_eq!(
+        Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1"),
+        Err(FenError::WrongRankCount(7))
+    );
+    assert_eq!(
+        Game::from_fen("rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        Err(FenError::BadRankLength { rank: 6, length: 7 })
+    );
+    assert_eq!(
+        Game::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        Err(FenError::UnknownPiece('9'))
+    );
+    assert_eq!(
+        Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKXNR w KQkq - 0 1"),
+        Err(FenError::UnknownPiece('X'))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/K3K3 w - - 0 1"),
+        Err(FenError::KingCount { color: 0, count: 2 })
+    );
+    assert_eq!(
+        Game::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenError::KingCount { color: 1, count: 0 })
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+        Err(FenError::InvalidSideToMove("x".to_string()))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4K3 w KA - 0 1"),
+        Err(FenError::InvalidCastling("KA".to_string()))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - e3 0 1"),
+        Err(FenError::InvalidEnPassant("e3".to_string()))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+        Err(FenError::InvalidHalfmoveClock("x".to_string()))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+        Err(FenError::InvalidFullmoveNumber("0".to_string()))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4K2P w - - 0 1"),
+        Err(FenError::PawnOnBackRank((7usize, 0).into()))
+    );
+    assert_eq!(
+        Game::from_fen("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1"),
+        Err(FenError::OpponentInCheck)
+    );
+    assert_eq!(Game::from_fen("4k3/8/8/8/8/8/8/4K3"), Err(FenError::MissingField("side to move")));
+}
+
+#[test]
+fn tracks_state_after_moves() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+
+    game.make_pmove(input!("e4")?)?;
+    assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    game.make_pmove(input!("c5")?)?;
+    game.make_pmove(input!("nf3")?)?;
+    assert_eq!(game.to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    game.make_pmove(input!("nc6")?)?;
+    game.make_pmove(input!("rg1")?)?;
+    assert_eq!(game.to_fen(), "r1bqkbnr/pp1ppppp/2n5/2p5/4P3/5N2/PPPP1PPP/RNBQKBR1 b Qkq - 3 3");
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use super::*;
+
+fn play(game: &mut Game, moves: &[&str]) -> Result<(), Box<dyn Error>> {
+    for san in moves {
+        game.make_pmove(input!(san)?)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn parses_san() -> Result<(), MoveError> {
+    let nbd7 = Input::build("Nbd7")?;
+    assert_eq!(nbd7.piece_type, PieceType::Knight);
+    assert_eq!((nbd7.start_file, nbd7.start_rank), (Some(1), None));
+    assert_eq!(nbd7.end_position, (3usize, 6).into());
+
+    let r1e2 = Input::build("R1e2")?;
+    assert_eq!((r1e2.start_file, r1e2.start_rank), (None, Some(0)));
+
+    let qh4e1 = Input::build("Qh4xe1")?;
+    assert_eq!((qh4e1.start_file, qh4e1.start_rank), (Some(7), Some(3)));
+    assert!(qh4e1.is_capture);
+
+    let exd5 = Input::build("exd5")?;
+    assert_eq!(exd5.piece_type, PieceType::Pawn);
+    assert_eq!(exd5.start_file, Some(4));
+    assert!(exd5.is_capture);
+
+    for promotion in ["e8=Q", "e8Q", "e8q+"] {
+        assert_eq!(Input::build(promotion)?.promotion, Some(PieceType::Queen));
+    }
+    assert_eq!(Input::build("bxa1=N#")?.promotion, Some(PieceType::Knight));
+    assert_eq!(Input::build("Nf3+")?.end_position, (5usize, 2).into());
+    assert_eq!(Input::build("Qxf7#")?.piece_type, PieceType::Queen);
+    assert_eq!(Input::build("0-0")?.castling, 1);
+    assert_eq!(Input::build("O-O-O")?.castling, 2);
+    assert_eq!(Input::build("Nf3!?")?.piece_type, PieceType::Knight);
+    Ok(())
+}
+
+#[test]
+fn lowercase_pieces_still_accepted() -> Result<(), MoveError> {
+    assert_eq!(Input::build("nf3")?.piece_type, PieceType::Knight);
+    assert_eq!(Input::build("bb4")?.piece_type, PieceType::Bishop);
+    assert_eq!(Input::build("bxb4")?.piece_type, PieceType::Bishop);
+    assert_eq!(Input::build("bxc4")?.piece_type, PieceType::Pawn);
+    assert_eq!(Input::build("pxd4")?.piece_type, PieceType::Pawn);
+    Ok(())
+}
+
+#[test]
+fn rejects_bad_san() {
+    assert_eq!(Input::build("e"), Err(MoveError::Parse("Invalid input")));
+    assert_eq!(Input::build("e9"), Err(MoveError::Parse("Invalid y coordinate")));
+    assert_eq!(Input::build("Nz3"), Err(MoveError::Parse("Invalid x coordinate")));
+    assert_eq!(Input::build("e8=K"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(Input::build("Nf3=Q"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(Input::build("a8"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(Input::build("bxa1"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(Input::build("a3=Q"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(Input::build("a3Q"), Err(MoveError::Parse("Invalid move")));
+    assert_eq!(Input::build("Nbbd7"), Err(MoveError::Parse("Invalid input")));
+}
+
+#[test]
+fn garbage_is_not_a_promotion() {
+    for garbage in ["foo", "o-o", "Nf3x", "e4e", "Qh4ex"] {
+        assert_eq!(Input::build(garbage), Err(MoveError::Parse("Invalid move")), "{}", garbage);
+    }
+}
+
+#[test]
+fn hints_choose_between_candidates() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("8/8/1k6/8/4Q2Q/8/8/K6Q w - - 0 1")?;
+    assert!(game.make_pmove(input!("Qe1")?).is_err());
+    assert!(game.make_pmove(input!("Qhe1")?).is_err());
+    assert!(game.make_pmove(input!("Q4e1")?).is_err());
+    play(&mut game, &["Qh4e1"])?;
+    assert_eq!(game[(4usize, 0)].byte, 0b0101);
+    assert_eq!(game[(7usize, 3)].byte, 0);
+
+    let mut game = Game::from_fen("k7/8/8/8/8/4R3/8/4R1K1 w - - 0 1")?;
+    assert!(game.make_pmove(input!("Re2")?).is_err());
+    play(&mut game, &["R1e2"])?;
+    assert_eq!(game[(4usize, 2)].byte, 0b0010);
+
+    let mut game = Game::default();
+    play(&mut game, &["d4", "d5", "Nf3", "Nf6", "Nbd2", "Nbd7"])?;
+    assert_eq!(game[(3usize, 1)].byte, 0b0011);
+    assert_eq!(game[(3usize, 6)].byte, 0b1011);
+    Ok(())
+}
+
+#[test]
+fn pawn_captures_and_marks() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    play(&mut game, &["e4", "d5", "c4", "c6", "exd5"])?;
+    assert_eq!(game[(3usize, 4)].byte, 0b0001);
+    assert_eq!(game[(2usize, 3)].byte, 0b0001);
+
+    let mut game = Game::default();
+    play(&mut game, &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"])?;
+    assert!(game.check_win());
+
+    let mut game = Game::from_fen("8/4P2k/8/8/8/8/8/4K3 w - - 0 1")?;
+    play(&mut game, &["e8=N"])?;
+    assert_eq!(game[(4usize, 7)].byte, 0b0011);
+    Ok(())
+}
+
+#[test]
+fn promotion_only_on_the_last_rank() -> Result<(), FenError> {
+    for (fen, san) in [
+        ("k7/P7/8/8/8/8/P7/K7 w - - 0 1", "a8"),
+        ("k7/P7/8/8/8/8/P7/K7 w - - 0 1", "a3=Q"),
+        ("k7/p7/8/8/8/8/p7/7K b - - 0 1", "a1"),
+        ("k7/p7/8/8/8/8/p7/7K b - - 0 1", "a6=N"),
+    ] {
+        let mut game = Game::from_fen(fen)?;
+        assert_eq!(
+            input!(san).and_then(|pmove| game.make_pmove(pmove)),
+            Err(MoveError::Parse("Invalid promotion")),
+            "{}",
+            san
+        );
+    }
+    Ok(())
+}
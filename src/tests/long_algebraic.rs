@@ -0,0 +1,61 @@
+use super::*;
+
+#[test]
+fn parses_and_plays_coordinate_moves() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    for uci in ["e2e4", "d7d5", "e4d5", "g8f6"] {
+        let pmove = game.parse_uci(uci)?;
+        assert_eq!(pmove.to_uci(), uci);
+        game.make_pmove(pmove)?;
+    }
+
+    assert_eq!(game.moves[2].piece_type(), PieceType::Pawn);
+    assert!(game.moves[2].is_capture());
+    assert_eq!(game.to_fen(), "rnbqkb1r/ppp1pppp/5n2/3P4/8/8/PPPP1PPP/RNBQKBNR w KQkq - 1 3");
+    Ok(())
+}
+
+#[test]
+fn promotion_and_castling() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("8/4P1k1/8/8/8/8/8/R3K2R w KQ - 0 1")?;
+
+    let promotion = game.parse_uci("e7e8n")?;
+    assert_eq!(promotion.promotion(), PieceType::Knight);
+    assert_eq!(promotion.to_uci(), "e7e8n");
+    assert_eq!(game.parse_uci("e7e8"), Err(MoveError::Parse("Invalid promotion")));
+
+    let castle = game.parse_uci("e1g1")?;
+    assert_eq!(castle.castling(), 1);
+    assert_eq!(castle.to_uci(), "e1g1");
+    assert_eq!(game.parse_uci("e1c1")?.castling(), 2);
+
+    game.make_pmove(promotion)?;
+    assert_eq!(game[(4usize, 7)].byte, 0b0011);
+    Ok(())
+}
+
+#[test]
+fn round_trips_generated_moves() -> Result<(), FenError> {
+    let game = Game::from_fen(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    )?;
+
+    for pmove in game.get_valid_moves() {
+        assert_eq!(game.parse_uci(&pmove.to_uci()), Ok(pmove));
+    }
+    Ok(())
+}
+
+#[test]
+fn rejects_bad_coordinates() {
+    let game = Game::default();
+
+    assert_eq!(game.parse_uci("e2"), Err(MoveError::Parse("Invalid move")));
+    assert_eq!(game.parse_uci("i2e4"), Err(MoveError::Parse("Invalid start square")));
+    assert_eq!(game.parse_uci("e2e9"), Err(MoveError::Parse("Invalid end square")));
+    assert_eq!(game.parse_uci("e7e8k"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(game.parse_uci("a2a3q"), Err(MoveError::Parse("Invalid promotion")));
+    assert_eq!(game.parse_uci("e3e4"), Err(MoveError::NoPiece(parse_square("e3").unwrap())));
+    assert_eq!(game.parse_uci("e7e5"), Err(MoveError::NoPiece(parse_square("e7").unwrap())));
+    assert!(game.clone().make_pmove(game.parse_uci("e2e5").unwrap()).is_err());
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+fn legal_uci(game: &Game) -> Vec<String> {
+    let mut moves = game
+        .get_valid_moves()
+        .iter()
+        .map(|pmove| pmove.to_uci())
+        .collect::<Vec<_>>();
+    moves.sort();
+    moves
+}
+
+#[test]
+fn generates_castling_on_both_sides() -> Result<(), FenError> {
+    let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")?;
+    let moves = legal_uci(&game);
+
+    assert!(moves.contains(&"e1g1".to_string()));
+    assert!(moves.contains(&"e1c1".to_string()));
+    Ok(())
+}
+
+#[test]
+fn no_castling_through_attacked_squares_or_without_rights() -> Result<(), FenError> {
+    let through_check = Game::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQ - 0 1")?;
+    assert!(!legal_uci(&through_check).contains(&"e1g1".to_string()));
+    assert!(legal_uci(&through_check).contains(&"e1c1".to_string()));
+
+    let no_rights = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Q - 0 1")?;
+    assert!(!legal_uci(&no_rights).contains(&"e1g1".to_string()));
+    Ok(())
+}
+
+#[test]
+fn generates_every_promotion() -> Result<(), FenError> {
+    let game = Game::from_fen("1r5k/P7/8/8/8/8/8/K7 w - - 0 1")?;
+    let promotions = legal_uci(&game)
+        .into_iter()
+        .filter(|uci| uci.len() == 5)
+        .collect::<Vec<_>>();
+
+    assert_eq!(promotions, [
+        "a7a8b", "a7a8n", "a7a8q", "a7a8r", "a7b8b", "a7b8n", "a7b8q", "a7b8r",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn generates_en_passant_only_onto_the_skipped_square() -> Result<(), MoveError> {
+    let mut game = Game::default();
+    for uci in ["e2e4", "a7a6", "e4e5", "d7d5"] {
+        let pmove = game.parse_uci(uci)?;
+        game.make_pmove(pmove)?;
+    }
+    let moves = legal_uci(&game);
+
+    assert!(moves.contains(&"e5d6".to_string()));
+    assert!(!moves.contains(&"e5f6".to_string()));
+    Ok(())
+}
+
+#[test]
+fn pinned_pieces_stay_on_the_pin() -> Result<(), FenError> {
+    let game = Game::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1")?;
+
+    assert!(legal_uci(&game).iter().all(|uci| !uci.starts_with("e2")));
+    Ok(())
+}
+
+#[test]
+fn detects_checkmate_and_stalemate() -> Result<(), FenError> {
+    let mate = Game::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1")?;
+    assert!(mate.check_win());
+
+    let stalemate = Game::from_fen("7k/5Q2/8/8/8/8/8/6K1 b - - 0 1")?;
+    assert!(stalemate.get_valid_moves().is_empty());
+    assert!(!stalemate.check_win());
+    assert!(stalemate.check_draw());
+    Ok(())
+}
+
+#[test]
+fn en_passant_from_fen() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2")?;
+    assert!(legal_uci(&game).contains(&"e5d6".to_string()));
+
+    game.make_pmove(input!("exd6")?)?;
+    assert_eq!(game.to_fen(), "4k3/8/3P4/8/8/8/8/4K3 b - - 0 2");
+
+    let without_target = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 2")?;
+    assert!(!legal_uci(&without_target).contains(&"e5d6".to_string()));
+    Ok(())
+}
+
+#[test]
+fn castling_allowed_checks_king_path() -> Result<(), FenError> {
+    let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")?;
+    assert!(game.castling_allowed(1));
+    assert!(game.castling_allowed(2));
+
+    let in_check = Game::from_fen("r3k2r/8/8/8/8/8/4r3/R3K2R w KQ - 0 1")?;
+    assert!(!in_check.castling_allowed(1));
+    assert!(!in_check.castling_allowed(2));
+
+    // b1 may be attacked, only the squares the king crosses matter
+    let b_file_attacked = Game::from_fen("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1")?;
+    assert!(b_file_attacked.castling_allowed(2));
+
+    let blocked = Game::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1")?;
+    assert!(!blocked.castling_allowed(2));
+    Ok(())
+}
+
+#[test]
+fn capturing_a_rook_removes_its_castling_right() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")?;
+    game.make_pmove(input!("Rxh8+")?)?;
+
+    assert_eq!(game.castling_rights, CastlingRights(WHITE_QUEENSIDE | BLACK_QUEENSIDE));
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use super::*;
+
+fn picked(game: &Game, hash_move: Option<PMove>, heuristics: &Heuristics) -> Vec<PMove> {
+    let mut picker = MovePicker::new(game, hash_move, heuristics, 0);
+    std::iter::from_fn(|| picker.next(game, heuristics)).collect()
+}
+
+fn sorted(mut moves: Vec<PMove>) -> Vec<u32> {
+    moves.sort_by_key(|pmove| pmove.0);
+    moves.into_iter().map(|pmove| pmove.0).collect()
+}
+
+#[test]
+fn move_kinds_split_all_moves() -> Result<(), FenError> {
+    for position in PERFT_SUITE.iter() {
+        let game = Game::from_fen(position.fen)?;
+        let own = game.bitboards().colors[game.turn as usize];
+        let noisy = game.generate_moves(own, MoveKind::Noisy);
+        let quiet = game.generate_moves(own, MoveKind::Quiet);
+        assert!(noisy.iter().all(PMove::is_noisy));
+        assert!(quiet.iter().all(|pmove| !pmove.is_noisy()));
+        let all = sorted(game.get_valid_moves());
+        assert_eq!(sorted([noisy, quiet].concat()), all, "{}", position.name);
+    }
+    Ok(())
+}
+
+#[test]
+fn picker_hands_out_every_move_once() -> Result<(), FenError> {
+    for position in PERFT_SUITE.iter() {
+        let game = Game::from_fen(position.fen)?;
+        let moves = game.get_valid_moves();
+        let mut heuristics = Heuristics::new();
+        let quiets = moves.iter().filter(|pmove| !pmove.is_noisy()).copied().collect::<Vec<_>>();
+        heuristics.update(&game, 0, 4, quiets[0], &quiets[..1]);
+        heuristics.update(&game, 0, 4, quiets[1], &quiets[..2]);
+
+        for hash_move in [None, moves.first().copied(), Some(quiets[0]), Some(PMove::castle(2))] {
+            assert_eq!(
+                sorted(picked(&game, hash_move, &heuristics)),
+                sorted(moves.clone()),
+                "{}",
+                position.name
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn picker_stages() -> Result<(), FenError> {
+    // Rxd5 wins a pawn with the queen's backing, Bxh5 loses the bishop to g6
+    let game = Game::from_fen("3rk3/8/6p1/3p3p/8/8/3RB3/3QK3 w - - 0 1")?;
+    let moves = picked(&game, None, &Heuristics::new());
+    let uci = moves.iter().map(|pmove| pmove.to_uci()).collect::<Vec<_>>();
+    assert_eq!(uci.len(), game.get_valid_moves().len());
+    let first_quiet = moves.iter().position(|pmove| !pmove.is_noisy()).unwrap();
+    assert!(moves[..first_quiet].iter().all(PMove::is_noisy));
+    assert_eq!(uci[0], "d2d5");
+    assert_eq!(uci.last().unwrap(), "e2h5");
+
+    let mut heuristics = Heuristics::new();
+    let killer = *game.get_valid_moves().iter().find(|pmove| pmove.to_uci() == "e1f2").unwrap();
+    heuristics.update(&game, 0, 3, killer, &[killer]);
+    let moves = picked(&game, None, &heuristics);
+    assert_eq!(moves[first_quiet], killer);
+    Ok(())
+}
+
+#[test]
+fn quiescence_picker_skips_losing_captures() -> Result<(), FenError> {
+    let game = Game::from_fen("3rk3/8/8/3p4/8/8/8/3QK3 w - - 0 1")?;
+    let mut picker = MovePicker::noisy();
+    assert_eq!(picker.next(&game, &Heuristics::new()), None);
+
+    let game = Game::from_fen("3rk3/8/8/3p4/8/8/3R4/3QK3 w - - 0 1")?;
+    let mut picker = MovePicker::noisy();
+    let heuristics = Heuristics::new();
+    let moves = std::iter::from_fn(|| picker.next(&game, &heuristics))
+        .map(|pmove| pmove.to_uci())
+        .collect::<Vec<_>>();
+    assert_eq!(moves, ["d2d5"]);
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn perft_counts_legal_moves() {
+    let mut game = Game::default();
+
+    assert_eq!(game.perft(0), 1);
+    assert_eq!(game.perft(1), 20);
+    assert_eq!(game, Game::default());
+}
+
+#[test]
+fn divide_sums_to_perft() -> Result<(), FenError> {
+    let mut game = Game::from_fen(PERFT_SUITE[2].fen)?;
+    let before = game.clone();
+    let divide = game.divide(2);
+
+    assert_eq!(divide.len(), 14);
+    assert_eq!(
+        divide
+            .iter()
+            .map(|(_, nodes)| nodes)
+            .sum::<u64>(),
+        game.perft(2)
+    );
+    assert_eq!(game, before);
+    Ok(())
+}
+
+#[test]
+fn suite_at_depth_one() {
+    for position in PERFT_SUITE.iter() {
+        assert_eq!(position.verify(1), Ok(position.nodes[0]), "{}", position.name);
+    }
+}
+
+#[test]
+fn suite_at_depth_two() {
+    for position in PERFT_SUITE.iter() {
+        assert_eq!(position.verify(2), Ok(position.nodes[1]), "{}", position.name);
+    }
+}
@@ -0,0 +1,163 @@
+use super::*;
+use std::io::Cursor;
+
+const SCHOLARS_MATE: &str = r#"[Event "Casual game"]
+[Site "?"]
+[Date "2023.01.01"]
+[Round "1"]
+[White "Alice"]
+[Black "Bob \"the brave\""]
+[Result "1-0"]
+
+{Opening comment} 1. e4 e5 2. Bc4 Nc6 (2... Nf6 3. d3 {quiet}) 3. Qh5 Nf6?? $18
+; resigns next move
+4. Qxf7# 1-0
+"#;
+
+#[test]
+fn parses_tags_comments_and_nags() -> Result<(), PgnError> {
+    let pgn = PgnGame::parse(SCHOLARS_MATE)?;
+
+    assert_eq!(pgn.tag("White"), Some("Alice"));
+    assert_eq!(pgn.tag("Black"), Some("Bob \"the brave\""));
+    assert_eq!(pgn.comment.as_deref(), Some("Opening comment"));
+    assert_eq!(
+        pgn.moves
+            .iter()
+            .map(|m| m.san.as_str())
+            .collect::<Vec<_>>(),
+        ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]
+    );
+    assert_eq!(pgn.moves[5].nags, [4, 18]);
+    assert_eq!(pgn.moves[5].comment.as_deref(), Some("resigns next move"));
+    assert_eq!(pgn.result, "1-0");
+    Ok(())
+}
+
+#[test]
+fn replays_through_make_pmove() -> Result<(), PgnError> {
+    let game = PgnGame::parse(SCHOLARS_MATE)?.to_game()?;
+
+    assert_eq!(game.moves.len(), 7);
+    assert!(game.check_win());
+    assert_eq!(
+        game.to_fen(),
+        "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4"
+    );
+    Ok(())
+}
+
+#[test]
+fn writes_game_with_result() -> Result<(), PgnError> {
+    let game = PgnGame::parse(SCHOLARS_MATE)?.to_game()?;
+
+    assert_eq!(
+        PgnGame::from_game(&game).to_string(),
+        r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "?"]
+[Black "?"]
+[Result "1-0"]
+
+1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn writes_disambiguated_san() -> Result<(), PgnError> {
+    let game = PgnGame::parse("1. Nf3 Nf6 2. Nc3 Nc6 3. Nd4 Nd5 4. Ndb5 Ncb4 *")?.to_game()?;
+    let pgn = PgnGame::from_game(&game);
+
+    assert_eq!(pgn.moves[6].san, "Ndb5");
+    assert_eq!(pgn.moves[7].san, "Ncb4");
+    assert_eq!(pgn.result, "*");
+    Ok(())
+}
+
+#[test]
+fn writes_fen_tags_and_black_first_move() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 b - - 0 30")?;
+    game.make_pmove(input!("kd7")?)?;
+    game.make_pmove(input!("ra7")?)?;
+    let pgn = PgnGame::from_game(&game).to_string();
+
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/R3K3 b - - 0 30\"]"));
+    assert!(pgn.ends_with("\n30... Kd7 31. Ra7+ *\n"));
+    assert_eq!(PgnGame::parse(&pgn)?.to_game()?, game);
+    Ok(())
+}
+
+#[test]
+fn fifty_move_rule_sets_the_result() -> Result<(), Box<dyn Error>> {
+    for (halfmove_clock, result) in [(98, "*"), (99, "1/2-1/2")] {
+        let fen = format!("k7/8/8/8/8/8/1R6/K7 w - - {} 80", halfmove_clock);
+        let mut game = Game::from_fen(&fen)?;
+        game.make_pmove(input!("Rc2")?)?;
+
+        let pgn = PgnGame::from_game(&game);
+        assert_eq!(pgn.tag("Result"), Some(result));
+        assert_eq!(pgn.result, result);
+    }
+    Ok(())
+}
+
+#[test]
+fn streams_multiple_games() {
+    let text = format!("{}\n{}\n[Event \"Third\"]\n\n1. d4 *\n", SCHOLARS_MATE, SCHOLARS_MATE);
+    let games = PgnReader::new(Cursor::new(text))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[1].moves.len(), 7);
+    assert_eq!(games[2].tag("Event"), Some("Third"));
+    assert_eq!(games[2].moves, [PgnMove::new("d4")]);
+}
+
+#[test]
+fn brackets_in_movetext_dont_start_a_game() {
+    let text = "[Event \"First\"]\n\n1. e4 {see\n\n[note] below} e5 2. Nf3 *\n\n\
+                [Event \"Second\"]\n\n1. d4 *\n";
+    let games = PgnReader::new(Cursor::new(text))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].moves.len(), 3);
+    assert_eq!(games[0].moves[0].comment.as_deref(), Some("see\n\n[note] below"));
+    assert_eq!(games[1].tag("Event"), Some("Second"));
+}
+
+#[test]
+fn games_end_at_their_result() {
+    let text = "[Event \"A\"]\n\n1. e4 e5 1-0\n[Event \"B\"]\n\n1. d4 *\n";
+    let games = PgnReader::new(Cursor::new(text))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(games.len(), 2);
+    assert_eq!((games[0].tag("Event"), games[0].moves.len()), (Some("A"), 2));
+    assert_eq!((games[1].tag("Event"), games[1].moves.len()), (Some("B"), 1));
+
+    let text = "1. e4 {not 1-0 yet} e5 1-0\n\n1. d4 d5 2. c4 1/2-1/2\n";
+    let games = PgnReader::new(Cursor::new(text))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(games.len(), 2);
+    assert_eq!((games[0].moves.len(), games[0].result.as_str()), (2, "1-0"));
+    assert_eq!((games[1].moves.len(), games[1].result.as_str()), (3, "1/2-1/2"));
+}
+
+#[test]
+fn reports_illegal_moves() {
+    let result = PgnGame::parse("1. e4 e5 2. Ke3 *").unwrap().to_game();
+
+    assert!(
+        matches!(result, Err(PgnError::IllegalMove { ply: 3, san }) if san == "Ke3")
+    );
+    assert!(matches!(PgnGame::parse("1. e4 {unfinished"), Err(PgnError::UnterminatedComment)));
+    assert!(matches!(PgnGame::parse("[Event]"), Err(PgnError::InvalidTag(_))));
+}
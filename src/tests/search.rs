@@ -0,0 +1,247 @@
+use super::*;
+use std::time::{ Duration, Instant };
+
+fn search_fen(fen: &str, limits: SearchLimits) -> Result<SearchResult, FenError> {
+    let mut game = Game::from_fen(fen)?;
+    let before = game.clone();
+    let result = search(&mut game, limits);
+    assert_eq!(game, before);
+    Ok(result)
+}
+
+#[test]
+fn finds_mate_in_one() -> Result<(), FenError> {
+    let result = search_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", SearchLimits::depth(3))?;
+
+    assert_eq!(result.best_move.map(|pmove| pmove.to_uci()), Some("a1a8".to_string()));
+    assert_eq!(result.score, MATE - 1);
+    assert_eq!(mate_in(result.score), Some(1));
+    Ok(())
+}
+
+#[test]
+fn finds_mate_in_two() -> Result<(), FenError> {
+    // Rook roller: 1. Rb7 Kg8 2. Ra8#
+    let result = search_fen("7k/8/8/8/8/8/1R6/R6K w - - 0 1", SearchLimits::depth(4))?;
+
+    assert_eq!(mate_in(result.score), Some(2));
+    assert_eq!(result.pv.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn sees_being_mated() -> Result<(), FenError> {
+    let result = search_fen("6k1/5ppp/8/8/8/8/r7/1r4K1 w - - 0 1", SearchLimits::depth(2))?;
+
+    assert_eq!(result.score, -MATE);
+    assert_eq!(result.best_move, None);
+    Ok(())
+}
+
+#[test]
+fn stalemate_scores_zero() -> Result<(), FenError> {
+    let result = search_fen("7k/5Q2/8/8/8/8/8/6K1 b - - 0 1", SearchLimits::depth(2))?;
+
+    assert_eq!(result.score, 0);
+    assert_eq!(result.best_move, None);
+    Ok(())
+}
+
+#[test]
+fn wins_hanging_material_and_avoids_losing_it() -> Result<(), FenError> {
+    let take = search_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", SearchLimits::depth(1))?;
+    assert_eq!(take.best_move.map(|pmove| pmove.to_uci()), Some("d2d5".to_string()));
+
+    // Quiescence sees that Rxd5 loses the rook to cxd5
+    let poisoned = search_fen("4k3/8/2p5/3n4/8/8/3R4/4K3 w - - 0 1", SearchLimits::depth(1))?;
+    assert_ne!(poisoned.best_move.map(|pmove| pmove.to_uci()), Some("d2d5".to_string()));
+    Ok(())
+}
+
+#[test]
+fn pv_is_playable() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let result = search(&mut game, SearchLimits::depth(3));
+
+    assert_eq!(result.depth, 3);
+    assert_eq!(result.best_move, result.pv.first().copied());
+    for pmove in result.pv {
+        game.make_pmove(pmove)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn respects_node_limit() {
+    let mut game = Game::default();
+    let result = search(&mut game, SearchLimits::nodes(2_000));
+
+    assert!(result.best_move.is_some());
+    assert!(result.nodes <= 2_000);
+}
+
+#[test]
+fn null_move_is_undone() -> Result<(), FenError> {
+    let mut game = Game::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1")?;
+    let before = game.clone();
+    game.make_null_move();
+    assert_eq!(game.turn, 1);
+    assert_eq!(game.en_passant, None);
+    assert_eq!(game.hash, game.zobrist());
+    game.unmake_null_move();
+    assert_eq!(game, before);
+    Ok(())
+}
+
+#[test]
+fn null_move_is_not_a_repetition() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("k7/8/8/8/8/8/8/K6R w - - 0 1")?;
+    for san in ["Rh2", "Kb8", "Rh3", "Ka8", "Rh1"] {
+        game.make_pmove(input!(san)?)?;
+    }
+    let before = game.clone();
+
+    // Black passing would bring back the start position, but only by an illegal move
+    game.make_null_move();
+    assert_eq!(game.halfmove_clock, 0);
+    assert_eq!(game.repetition_count(), 1);
+    game.unmake_null_move();
+    assert_eq!(game, before);
+    Ok(())
+}
+
+#[test]
+fn each_option_keeps_finding_mates() -> Result<(), FenError> {
+    let only = |set: fn(&mut SearchOptions)| {
+        let mut options = SearchOptions::none();
+        set(&mut options);
+        options
+    };
+    for options in [
+        SearchOptions::none(),
+        SearchOptions::default(),
+        only(|options| options.null_move = true),
+        only(|options| options.late_move_reductions = true),
+        only(|options| options.futility = true),
+        only(|options| options.reverse_futility = true),
+        only(|options| options.check_extensions = true),
+        only(|options| options.aspiration_windows = true),
+    ] {
+        let mut game = Game::from_fen("7k/8/8/8/8/8/1R6/R6K w - - 0 1")?;
+        let tt = TranspositionTable::new(1);
+        let result = search_with_options(&mut game, SearchLimits::depth(5), options, &tt);
+        assert_eq!(mate_in(result.score), Some(2), "{:?}", options);
+    }
+    Ok(())
+}
+
+#[test]
+fn selective_search_saves_nodes() {
+    let nodes = |options| {
+        let mut game = Game::from_fen(PERFT_SUITE[1].fen).unwrap();
+        let tt = TranspositionTable::new(1);
+        search_with_options(&mut game, SearchLimits::depth(5), options, &tt).nodes
+    };
+    assert!(nodes(SearchOptions::default()) < nodes(SearchOptions::none()));
+}
+
+#[test]
+fn single_thread_is_deterministic() {
+    let run = || {
+        let mut game = Game::from_fen(PERFT_SUITE[1].fen).unwrap();
+        let tt = TranspositionTable::new(1);
+        search_with_options(&mut game, SearchLimits::depth(4), SearchOptions::default(), &tt)
+    };
+    let (first, second) = (run(), run());
+    assert_eq!((first.pv, first.score, first.nodes), (second.pv, second.score, second.nodes));
+}
+
+#[test]
+fn threads_share_the_work() -> Result<(), FenError> {
+    let options = SearchOptions { threads: 4, ..SearchOptions::default() };
+    let mut game = Game::from_fen("7k/8/8/8/8/8/1R6/R6K w - - 0 1")?;
+    let before = game.clone();
+    let tt = TranspositionTable::new(1);
+    let result = search_with_options(&mut game, SearchLimits::depth(5), options, &tt);
+    assert_eq!(game, before);
+    assert_eq!(mate_in(result.score), Some(2));
+
+    let mut game = Game::from_fen(PERFT_SUITE[1].fen)?;
+    let single = search_with_options(
+        &mut game,
+        SearchLimits::depth(5),
+        SearchOptions::default(),
+        &TranspositionTable::new(1)
+    );
+    let parallel = search_with_options(&mut game, SearchLimits::depth(5), options, &tt);
+    assert!(parallel.best_move.is_some());
+    assert!(parallel.depth >= 5);
+    assert!(parallel.nodes > single.nodes / 2);
+    assert!(parallel.nps() > 0);
+
+    let limited = search_with_options(&mut game, SearchLimits::nodes(5_000), options, &tt);
+    assert!(limited.best_move.is_some());
+    Ok(())
+}
+
+#[test]
+fn budgets_the_clock() {
+    let minute = Duration::from_secs(60);
+    let budget = |limits: SearchLimits, turn| {
+        let time = TimeManager::new(&limits, turn, Instant::now());
+        (time.soft.unwrap(), time.hard.unwrap())
+    };
+
+    let (soft, hard) = budget(SearchLimits::clock(minute, Duration::ZERO), 0);
+    assert!(soft < hard && hard < minute);
+    let sudden_death = SearchLimits::clock(minute, Duration::ZERO);
+    let (last_move, _) = budget(SearchLimits { movestogo: Some(1), ..sudden_death }, 0);
+    assert!(last_move > soft && last_move < minute);
+    let (with_increment, _) = budget(SearchLimits::clock(minute, Duration::from_secs(1)), 0);
+    assert!(with_increment > soft);
+
+    // Black budgets its own clock
+    let short = SearchLimits { btime: Some(Duration::from_secs(1)), ..sudden_death };
+    assert!(budget(short, 1).1 < Duration::from_secs(1));
+    assert_eq!(budget(short, 0), (soft, hard));
+
+    let movetime = Duration::from_millis(300);
+    assert_eq!(budget(SearchLimits::movetime(movetime), 0), (movetime, movetime));
+    let infinite = TimeManager::new(&SearchLimits::infinite(), 0, Instant::now());
+    assert_eq!((infinite.soft, infinite.hard), (None, None));
+}
+
+#[test]
+fn instability_extends_the_soft_limit() -> Result<(), MoveError> {
+    let limits = SearchLimits::clock(Duration::from_secs(600), Duration::ZERO);
+    let soft = TimeManager::new(&limits, 0, Instant::now()).soft.unwrap();
+    // Past the soft limit, but not by much
+    let start = Instant::now() - soft.mul_f64(1.2);
+    let (e4, d4) = (input!("e4")?, input!("d4")?);
+
+    let mut stable = TimeManager::new(&limits, 0, start);
+    assert!(!stable.next_iteration(e4, 20));
+    let mut changed = TimeManager::new(&limits, 0, start);
+    changed.next_iteration(e4, 20);
+    assert!(changed.next_iteration(d4, 20));
+    let mut dropped = TimeManager::new(&limits, 0, start);
+    dropped.next_iteration(e4, 20);
+    assert!(dropped.next_iteration(e4, -80));
+    Ok(())
+}
+
+#[test]
+fn timed_search_stops_in_time() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen(PERFT_SUITE[1].fen)?;
+    let limits = SearchLimits::clock(Duration::from_secs(3), Duration::ZERO);
+    let hard = TimeManager::new(&limits, game.turn, Instant::now()).hard.unwrap();
+    let result = search(&mut game, limits);
+
+    // Stopping mid-iteration still leaves a playable line
+    assert!(result.time < hard + Duration::from_millis(200));
+    assert_eq!(result.best_move, result.pv.first().copied());
+    for pmove in result.pv {
+        game.make_pmove(pmove)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use super::*;
+
+fn see(fen: &str, uci: &str) -> i32 {
+    let game = Game::from_fen(fen).unwrap();
+    let pmove = game.get_valid_moves().into_iter().find(|pmove| pmove.to_uci() == uci).unwrap();
+    game.see(pmove)
+}
+
+#[test]
+fn simple_exchanges() {
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+    assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), -220);
+    assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), 0);
+}
+
+#[test]
+fn x_ray_attackers_join_in() {
+    assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5"), -400);
+    assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+    // The queen behind the bishop wins back the pawn that recaptures
+    assert_eq!(see("4k3/8/5p2/4p3/8/8/1B6/4K3 w - - 0 1", "b2e5"), -230);
+    assert_eq!(see("4k3/8/5p2/4p3/8/8/1B6/Q3K3 w - - 0 1", "b2e5"), -130);
+}
+
+#[test]
+fn king_only_recaptures_when_safe() {
+    assert_eq!(see("4k3/3p4/8/8/8/8/3Q4/4K3 w - - 0 1", "d2d7"), -800);
+    assert_eq!(see("4k3/3p4/8/8/8/8/3Q4/3RK3 w - - 0 1", "d2d7"), 100);
+}
+
+#[test]
+fn en_passant_and_promotions() {
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    assert_eq!(see("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), 800);
+    assert_eq!(see("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), -100);
+    assert_eq!(see("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"), 1300);
+}
@@ -0,0 +1,79 @@
+use super::*;
+
+#[test]
+fn stores_and_probes() -> Result<(), MoveError> {
+    let tt = TranspositionTable::new(1);
+    let game = Game::default();
+    let e4 = game.parse_uci("e2e4")?;
+    tt.store(game.hash, 0, Some(e4), 5, Bound::Exact, 35);
+
+    assert_eq!(
+        tt.probe(game.hash, 0),
+        Some(TtEntry { best_move: Some(e4), depth: 5, bound: Bound::Exact, score: 35 })
+    );
+    assert_eq!(tt.probe(game.hash ^ 1, 0), None);
+    Ok(())
+}
+
+#[test]
+fn sized_in_megabytes() {
+    assert_eq!(TranspositionTable::new(1).len(), 1024 * 1024 / 16);
+    assert_eq!(TranspositionTable::new(0).len(), 1);
+}
+
+#[test]
+fn mate_scores_are_relative_to_the_node() {
+    let tt = TranspositionTable::new(1);
+    // Mate in 3 plies from a node at ply 4 is mate in 7 plies from the root
+    tt.store(42, 4, None, 3, Bound::Exact, MATE - 7);
+
+    assert_eq!(tt.probe(42, 4).map(|entry| entry.score), Some(MATE - 7));
+    assert_eq!(tt.probe(42, 0).map(|entry| entry.score), Some(MATE - 3));
+    tt.store(43, 2, None, 3, Bound::Exact, -MATE + 5);
+    assert_eq!(tt.probe(43, 6).map(|entry| entry.score), Some(-MATE + 9));
+}
+
+#[test]
+fn prefers_deeper_entries_within_a_search() {
+    // A single slot, so every position collides
+    let tt = TranspositionTable::new(0);
+    tt.store(1, 0, None, 6, Bound::Lower, 10);
+    tt.store(2, 0, None, 2, Bound::Lower, 20);
+    assert!(tt.probe(1, 0).is_some());
+    assert_eq!(tt.probe(2, 0), None);
+
+    tt.store(1, 0, None, 1, Bound::Upper, 30);
+    assert_eq!(tt.probe(1, 0).map(|entry| entry.score), Some(30));
+
+    tt.new_search();
+    tt.store(2, 0, None, 2, Bound::Lower, 20);
+    assert_eq!(tt.probe(2, 0).map(|entry| entry.depth), Some(2));
+}
+
+#[test]
+fn clear_and_hashfull() {
+    let tt = TranspositionTable::new(0);
+    assert_eq!(tt.hashfull(), 0);
+
+    let mut game = Game::default();
+    search_with_table(&mut game, SearchLimits::depth(2), &tt);
+    assert_eq!(tt.hashfull(), 1000);
+    tt.new_search();
+    assert_eq!(tt.hashfull(), 0);
+
+    search_with_table(&mut game, SearchLimits::depth(2), &tt);
+    tt.clear();
+    assert_eq!(tt.hashfull(), 0);
+    assert_eq!(tt.probe(game.hash, 0), None);
+}
+
+#[test]
+fn shared_table_saves_work() {
+    let tt = TranspositionTable::new(1);
+    let mut game = Game::default();
+    let first = search_with_table(&mut game, SearchLimits::depth(3), &tt);
+    let second = search_with_table(&mut game, SearchLimits::depth(3), &tt);
+
+    assert_eq!(first.best_move, second.best_move);
+    assert!(second.nodes < first.nodes);
+}
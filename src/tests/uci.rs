@@ -0,0 +1,107 @@
+use super::*;
+
+fn run(script: &str) -> Vec<String> {
+    let output = uci_loop(script.as_bytes(), Vec::new()).unwrap();
+    String::from_utf8(output).unwrap().lines().map(str::to_string).collect()
+}
+
+fn best_move(lines: &[String]) -> Option<&str> {
+    let best_moves = lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("bestmove "))
+        .collect::<Vec<_>>();
+    assert_eq!(best_moves.len(), 1, "{:?}", lines);
+    best_moves[0].split_whitespace().next()
+}
+
+#[test]
+fn handshake() {
+    let lines = run("uci\nisready\nquit\n");
+
+    assert!(lines[0].starts_with("id name chessrs"));
+    assert!(lines.iter().any(|line| line.starts_with("option name Hash type spin")));
+    assert!(lines.iter().any(|line| line.starts_with("option name Threads type spin")));
+    assert_eq!(lines[lines.len() - 2], "uciok");
+    assert_eq!(lines[lines.len() - 1], "readyok");
+}
+
+#[test]
+fn searches_position_after_moves() -> Result<(), Box<dyn Error>> {
+    let lines = run("position startpos moves e2e4 e7e5 g1f3\ngo depth 3\n");
+
+    let info = lines.iter().find(|line| line.starts_with("info depth 3 ")).unwrap();
+    for field in [" score cp ", " nodes ", " nps ", " hashfull ", " time ", " pv "] {
+        assert!(info.contains(field), "{}", info);
+    }
+    let mut game = Game::default();
+    for uci in ["e2e4", "e7e5", "g1f3", best_move(&lines).unwrap()] {
+        game.make_pmove(game.parse_uci(uci)?)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn reports_mate_scores() {
+    let lines = run("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo mate 1\n");
+
+    assert!(lines.iter().any(|line| line.contains(" score mate 1 ")));
+    assert_eq!(best_move(&lines), Some("a1a8"));
+}
+
+#[test]
+fn no_legal_moves_is_the_null_move() {
+    let lines = run("position fen 7k/5Q2/8/8/8/8/8/6K1 b - - 0 1\ngo depth 2\n");
+
+    assert_eq!(best_move(&lines), Some("0000"));
+}
+
+#[test]
+fn rejects_illegal_positions() {
+    let lines = run("position startpos moves e2e5\ngo depth 1\n");
+
+    assert!(lines[0].starts_with("info string e2e5"));
+    let best = best_move(&lines).unwrap();
+    assert!(Game::default().get_valid_moves().iter().any(|pmove| pmove.to_uci() == best));
+}
+
+#[test]
+fn promotion_has_to_match_the_move() {
+    let lines = run("position startpos moves a2a3q\ngo depth 1\n");
+    assert_eq!(lines[0], "info string a2a3q: Invalid promotion");
+    let best = best_move(&lines).unwrap();
+    assert!(Game::default().get_valid_moves().iter().any(|pmove| pmove.to_uci() == best));
+
+    let lines = run("position fen k7/P7/8/8/8/8/8/K7 w - - 0 1 moves a7a8\nisready\n");
+    assert_eq!(lines, ["info string a7a8: Invalid promotion", "readyok"]);
+}
+
+#[test]
+fn infinite_search_waits_for_stop() {
+    let lines = run("go infinite\nisready\nstop\n");
+
+    // The move only comes after stop, whatever the search found before
+    let ready = lines.iter().position(|line| line == "readyok").unwrap();
+    let best = lines.iter().position(|line| line.starts_with("bestmove ")).unwrap();
+    assert!(ready < best);
+    assert!(best_move(&lines).is_some());
+}
+
+#[test]
+fn ponderhit_plays_on_the_clock() {
+    let lines = run(
+        "setoption name Threads value 2\nsetoption name Hash value 1\n\
+         go ponder wtime 1000 btime 1000\nponderhit\n"
+    );
+
+    assert!(best_move(&lines).is_some());
+    assert!(!lines.iter().any(|line| line.starts_with("info string")));
+}
+
+#[test]
+fn timed_search_moves_in_time() {
+    let start = std::time::Instant::now();
+    let lines = run("go wtime 2000 btime 2000 winc 100 binc 100\n");
+
+    assert!(best_move(&lines).is_some());
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+fn assert_unmake_restores(fen: &str, moves: &[&str]) -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen(fen)?;
+    let mut snapshots = vec![];
+    for san in moves {
+        snapshots.push(game.clone());
+        game.make_pmove(input!(san)?)?;
+    }
+
+    while let Some(before) = snapshots.pop() {
+        assert!(game.unmake_move().is_some());
+        assert_eq!(game, before);
+    }
+    assert_eq!(game.unmake_move(), None);
+    Ok(())
+}
+
+#[test]
+fn restores_quiet_moves_and_captures() -> Result<(), Box<dyn Error>> {
+    assert_unmake_restores(START_FEN, &["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe6+", "Be2", "Qxe2+"])
+}
+
+#[test]
+fn restores_en_passant() -> Result<(), Box<dyn Error>> {
+    assert_unmake_restores(START_FEN, &["e4", "a6", "e5", "f5", "exf6", "Nxf6"])
+}
+
+#[test]
+fn restores_promotion_and_castling_rights() -> Result<(), Box<dyn Error>> {
+    assert_unmake_restores("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 5 20", &["bxa8=Q", "Kd7", "Rxh8"])
+}
+
+#[test]
+fn takes_back_several_moves() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    for san in ["e4", "e5", "Nf3", "Nc6"] {
+        game.make_pmove(input!(san)?)?;
+    }
+
+    let undone = game.takeback(3);
+    assert_eq!(
+        undone
+            .iter()
+            .map(|pmove| pmove.to_uci())
+            .collect::<Vec<_>>(),
+        ["b8c6", "g1f3", "e7e5"]
+    );
+    assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(game.takeback(5).len(), 1);
+    assert_eq!(game, Game::default());
+    Ok(())
+}
+
+#[test]
+fn undo_command() {
+    let mut game = Game::default();
+
+    assert_eq!(
+        handle_input(&mut game, &mut "undo".to_string()).unwrap_err_as_string(),
+        "Nothing to undo"
+    );
+    assert!(matches!(handle_input(&mut game, &mut "e4".to_string()), InProgress(Ok(()))));
+    assert!(matches!(handle_input(&mut game, &mut "undo 1".to_string()), InProgress(Ok(()))));
+    assert_eq!(game, Game::default());
+}
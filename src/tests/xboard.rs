@@ -0,0 +1,101 @@
+use super::*;
+
+fn run(script: &str) -> Vec<String> {
+    let output = xboard_loop(script.as_bytes(), Vec::new()).unwrap();
+    String::from_utf8(output).unwrap().lines().map(str::to_string).collect()
+}
+
+fn engine_moves(lines: &[String]) -> Vec<&str> {
+    lines.iter().filter_map(|line| line.strip_prefix("move ")).collect()
+}
+
+#[test]
+fn negotiates_features() {
+    let lines = run("xboard\nprotover 2\nping 7\nquit\n");
+
+    assert!(lines[0].starts_with("feature ") && lines[0].contains("usermove=1"));
+    assert_eq!(lines[1], "feature done=1");
+    assert_eq!(lines[2], "pong 7");
+}
+
+#[test]
+fn answers_user_moves() -> Result<(), Box<dyn Error>> {
+    let lines = run("new\nsd 2\npost\nusermove e2e4\nping 1\nusermove d2d4\n");
+
+    // Thinking output: depth, score, centiseconds, nodes, pv
+    let thinking = lines.iter().find(|line| line.starts_with("2 ")).unwrap();
+    assert!(thinking.split_whitespace().count() >= 5);
+    let moves = engine_moves(&lines);
+    assert_eq!(moves.len(), 2);
+
+    let mut game = Game::default();
+    for uci in ["e2e4", moves[0], "d2d4", moves[1]] {
+        game.make_pmove(game.parse_uci(uci)?)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn force_mode_only_records_moves() {
+    let lines = run("new\nforce\nusermove e2e4\nusermove e7e5\ne2e5\nsd 1\ngo\n");
+
+    assert_eq!(lines[0], "Illegal move: e2e5");
+    // `go` plays the side to move, white after 1. e4 e5
+    let moves = engine_moves(&lines);
+    assert_eq!(moves.len(), 1);
+    let mut game = Game::default();
+    game.make_pmove(input!("e4").unwrap()).unwrap();
+    game.make_pmove(input!("e5").unwrap()).unwrap();
+    assert!(game.get_valid_moves().iter().any(|pmove| pmove.to_uci() == moves[0]));
+}
+
+#[test]
+fn promotion_has_to_match_the_move() {
+    let lines = run(
+        "new\nforce\nusermove a2a3q\nsetboard k7/P7/8/8/8/8/8/K7 w - - 0 1\nusermove a7a8\nping 1\n"
+    );
+
+    assert_eq!(lines, ["Illegal move: a2a3q", "Illegal move: a7a8", "pong 1"]);
+}
+
+#[test]
+fn undo_and_setboard() {
+    let lines = run(
+        "new\nforce\nusermove e2e4\nundo\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\nsd 3\ngo\n"
+    );
+
+    assert_eq!(engine_moves(&lines), ["a1a8"]);
+    assert!(lines.contains(&"1-0 {White mates}".to_string()));
+
+    let lines = run("setboard not a fen\nremove\nping 2\n");
+    assert!(lines[0].starts_with("tellusererror Illegal position"));
+    assert_eq!(lines[1], "pong 2");
+}
+
+#[test]
+fn fifty_move_rule_ends_the_game() {
+    let lines = run(
+        "force\nsetboard k7/8/8/8/8/8/1R6/K7 w - - 98 80\nusermove b2c2\nping 1\n\
+         setboard k7/8/8/8/8/8/1R6/K7 w - - 99 80\nusermove b2c2\nping 2\n"
+    );
+
+    assert_eq!(lines, ["pong 1", "1/2-1/2 {Draw}", "pong 2"]);
+}
+
+#[test]
+fn plays_on_the_clock() {
+    let start = std::time::Instant::now();
+    let lines = run("new\nlevel 40 0:30 0\ntime 3000\notim 3000\ngo\n");
+
+    assert_eq!(engine_moves(&lines).len(), 1);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    // Told to move now, the engine doesn't use its ten seconds
+    let start = std::time::Instant::now();
+    let lines = run("new\nst 10\ngo\n?\n");
+    assert_eq!(engine_moves(&lines).len(), 1);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    let lines = run("new\nst 10\ngo\nresult 1-0 {White resigns}\n");
+    assert!(engine_moves(&lines).is_empty());
+}
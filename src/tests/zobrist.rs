@@ -0,0 +1,96 @@
+use super::*;
+
+#[test]
+fn matches_polyglot_keys() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    assert_eq!(game.hash, 0x463b96181691fc9c);
+
+    for (san, key) in [
+        ("e4", 0x823c9b50fd114196),
+        ("d5", 0x0756b94461c50fb0),
+        ("e5", 0x662fafb965db29d4),
+        ("f5", 0x22a48b5a8e47ff78),
+        ("Ke2", 0x652a607ca3f242c1),
+        ("Kf7", 0x00fdd303c946bdd9),
+    ] {
+        game.make_pmove(input!(san)?)?;
+        assert_eq!(game.hash, key, "after {}", san);
+    }
+
+    let mut game = Game::default();
+    for san in ["a4", "b5", "h4", "b4", "c4"] {
+        game.make_pmove(input!(san)?)?;
+    }
+    assert_eq!(game.hash, 0x3c8123ea7b067637);
+    game.make_pmove(input!("bxc3")?)?;
+    game.make_pmove(input!("Ra3")?)?;
+    assert_eq!(game.hash, 0x5c3f9b829b279560);
+    Ok(())
+}
+
+#[test]
+fn incremental_key_matches_full_key() -> Result<(), FenError> {
+    for position in PERFT_SUITE.iter() {
+        let mut game = Game::from_fen(position.fen)?;
+        for pmove in game.get_valid_moves() {
+            let before = game.clone();
+            assert!(game.make_pmove(pmove).is_ok());
+            assert_eq!(game.hash, game.zobrist(), "{} {}", position.name, pmove.to_uci());
+            game.unmake_move();
+            assert_eq!(game, before);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn transpositions_share_a_key() -> Result<(), Box<dyn Error>> {
+    let mut first = Game::default();
+    let mut second = Game::default();
+    for san in ["Nf3", "Nf6", "d4"] {
+        first.make_pmove(input!(san)?)?;
+    }
+    for san in ["d4", "Nf6", "Nf3"] {
+        second.make_pmove(input!(san)?)?;
+    }
+
+    assert_eq!(first.hash, second.hash);
+    Ok(())
+}
+
+#[test]
+fn threefold_repetition() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+        game.make_pmove(input!(san)?)?;
+        assert!(!game.check_draw());
+    }
+    game.make_pmove(input!("Ng8")?)?;
+
+    assert_eq!(game.repetition_count(), 3);
+    assert!(game.check_draw());
+    Ok(())
+}
+
+#[test]
+fn fifty_move_rule() -> Result<(), Box<dyn Error>> {
+    assert!(!Game::from_fen("k7/8/8/8/8/8/1R6/K7 w - - 99 80")?.check_draw());
+    assert!(Game::from_fen("k7/8/8/8/8/8/1R6/K7 w - - 100 80")?.check_draw());
+
+    let mut game = Game::from_fen("k7/8/8/8/8/8/1R6/K7 w - - 99 80")?;
+    game.make_pmove(input!("Rc2")?)?;
+    assert!(game.check_draw());
+    Ok(())
+}
+
+#[test]
+fn lost_castling_rights_change_the_position() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K2R w K - 0 1")?;
+    for san in ["Rh2", "Kd8", "Rh1", "Ke8"] {
+        game.make_pmove(input!(san)?)?;
+    }
+
+    // Same pieces as the start, but white has lost the right to castle
+    assert_eq!(game.repetition_count(), 1);
+    Ok(())
+}
@@ -0,0 +1,332 @@
+use crate::*;
+use std::io::BufRead;
+use std::str::FromStr;
+use std::sync::{ Arc, Mutex };
+use std::thread::{ self, JoinHandle };
+use std::time::Duration;
+
+pub(crate) const MAX_HASH_MB: usize = 4096;
+pub(crate) const MAX_THREADS: usize = 256;
+
+/// Speaks the Universal Chess Interface, reading commands from `input` and writing to
+/// `output` until `quit` or the end of the input. Searches run on their own thread, so `stop`,
+/// `ponderhit` and `isready` are answered while one is going. Returns `output` once the last
+/// search has reported its move.
+pub fn uci_loop<W: Write + Send + 'static>(input: impl BufRead, output: W) -> io::Result<W> {
+    let mut uci = Uci {
+        game: Game::default(),
+        tt: Arc::new(TranspositionTable::default()),
+        options: SearchOptions::default(),
+        output: Arc::new(Mutex::new(output)),
+        search: None,
+    };
+
+    for line in input.lines() {
+        if !uci.handle(&line?)? {
+            uci.stop()?;
+            break;
+        }
+    }
+    uci.wait()?;
+
+    match Arc::try_unwrap(uci.output) {
+        Ok(output) => Ok(output.into_inner().unwrap_or_else(|error| error.into_inner())),
+        Err(_) => unreachable!("search threads are joined"),
+    }
+}
+
+struct Uci<W> {
+    game: Game,
+    tt: Arc<TranspositionTable>,
+    options: SearchOptions,
+    output: Arc<Mutex<W>>,
+    search: Option<Search>,
+}
+
+/// A search running on its own thread, which sends `bestmove` when it is done.
+struct Search {
+    control: Arc<SearchControl>,
+    /// Whether the GUI will send `stop` before it wants the move.
+    infinite: bool,
+    handle: JoinHandle<io::Result<()>>,
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    /// Handles one line of input, returning false on `quit`. Unknown commands are ignored.
+    fn handle(&mut self, line: &str) -> io::Result<bool> {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let Some((command, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+
+        match *command {
+            "uci" => {
+                let author = env!("CARGO_PKG_AUTHORS").split('<').next().unwrap_or("").trim();
+                self.send(&format!("id name chessrs {}", env!("CARGO_PKG_VERSION")))?;
+                self.send(&format!("id author {}", author))?;
+                self.send(
+                    &format!(
+                        "option name Hash type spin default {} min 1 max {}",
+                        DEFAULT_HASH_MB,
+                        MAX_HASH_MB
+                    )
+                )?;
+                self.send(
+                    &format!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS)
+                )?;
+                self.send("option name Ponder type check default false")?;
+                self.send("option name Clear Hash type button")?;
+                self.send("uciok")?;
+            }
+            "isready" => self.send("readyok")?,
+            "setoption" => self.set_option(args)?,
+            "ucinewgame" => {
+                self.stop()?;
+                self.game = Game::default();
+                self.tt.clear();
+            }
+            "position" =>
+                match parse_position(args) {
+                    Ok(game) => {
+                        self.game = game;
+                    }
+                    Err(error) => self.send(&format!("info string {}", error))?,
+                }
+            "go" => self.go(args)?,
+            "stop" => self.stop()?,
+            "ponderhit" => {
+                if let Some(search) = &self.search {
+                    search.control.ponderhit();
+                }
+            }
+            "quit" => {
+                return Ok(false);
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn send(&self, line: &str) -> io::Result<()> {
+        send(&self.output, line)
+    }
+
+    /// `setoption name <name> value <value>`, where the name may contain spaces.
+    fn set_option(&mut self, args: &[&str]) -> io::Result<()> {
+        let value_at = args.iter().position(|arg| *arg == "value").unwrap_or(args.len());
+        let name = args[..value_at]
+            .iter()
+            .skip_while(|arg| **arg == "name")
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let value = args.get(value_at + 1..).unwrap_or_default().join(" ");
+
+        match name.to_lowercase().as_str() {
+            "hash" =>
+                match value.parse::<usize>() {
+                    // A running search keeps the old table until it is done
+                    Ok(megabytes) => {
+                        let megabytes = megabytes.clamp(1, MAX_HASH_MB);
+                        self.tt = Arc::new(TranspositionTable::new(megabytes));
+                    }
+                    Err(_) => self.send(&format!("info string Invalid hash size: {}", value))?,
+                }
+            "threads" =>
+                match value.parse::<usize>() {
+                    Ok(threads) => {
+                        self.options.threads = threads.clamp(1, MAX_THREADS);
+                    }
+                    Err(_) => {
+                        self.send(&format!("info string Invalid thread count: {}", value))?;
+                    }
+                }
+            // Pondering is up to the GUI, which sends `go ponder`
+            "ponder" => {}
+            "clear hash" => self.tt.clear(),
+            _ => self.send(&format!("info string Unknown option: {}", name))?,
+        }
+        Ok(())
+    }
+
+    fn go(&mut self, args: &[&str]) -> io::Result<()> {
+        self.stop()?;
+        let (limits, ponder) = parse_go(args);
+        let control = Arc::new(
+            if ponder { SearchControl::pondering() } else { SearchControl::default() }
+        );
+
+        let (mut game, options) = (self.game.clone(), self.options);
+        let (tt, output, search_control) = (self.tt.clone(), self.output.clone(), control.clone());
+        let handle = thread::spawn(move || {
+            let control = search_control;
+            let mut sent = Ok(());
+            let mut report = |result: &SearchResult| {
+                if sent.is_ok() {
+                    sent = send(&output, &info(result, tt.hashfull()));
+                }
+            };
+            let result = search_with_control(
+                &mut game,
+                limits,
+                options,
+                &tt,
+                &control,
+                &mut report
+            );
+            sent?;
+
+            // The GUI isn't ready for the move until it says `stop` or `ponderhit`
+            while (limits.infinite || control.is_pondering()) && !control.is_stopped() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            send(&output, &best_move(&result))
+        });
+
+        self.search = Some(Search { control, infinite: limits.infinite, handle });
+        Ok(())
+    }
+
+    /// Stops the running search, if any, and waits for it to send its move.
+    fn stop(&mut self) -> io::Result<()> {
+        if let Some(search) = &self.search {
+            search.control.stop();
+        }
+        self.wait()
+    }
+
+    /// Waits for the running search, if any, to send its move. A search that would wait for
+    /// `stop` forever is stopped.
+    fn wait(&mut self) -> io::Result<()> {
+        match self.search.take() {
+            Some(search) => {
+                if search.infinite || search.control.is_pondering() {
+                    search.control.stop();
+                }
+                search.handle.join().expect("search thread panicked")
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes `line` to the GUI, which may be shared with a search thread.
+pub(crate) fn send<W: Write>(output: &Mutex<W>, line: &str) -> io::Result<()> {
+    let mut output = output.lock().unwrap_or_else(|error| error.into_inner());
+    writeln!(output, "{}", line)?;
+    output.flush()
+}
+
+/// `position startpos|fen <fen> [moves <move>...]`, with moves in long algebraic notation.
+fn parse_position(args: &[&str]) -> Result<Game, Box<dyn Error>> {
+    let moves_at = args.iter().position(|arg| *arg == "moves").unwrap_or(args.len());
+    let mut game = match args.first() {
+        Some(&"startpos") => Game::default(),
+        Some(&"fen") => Game::from_fen(&args[1..moves_at].join(" "))?,
+        _ => {
+            return Err("Expected startpos or fen".into());
+        }
+    };
+
+    for uci in args.get(moves_at + 1..).unwrap_or_default() {
+        game.parse_uci(uci)
+            .and_then(|pmove| game.make_pmove(pmove))
+            .map_err(|error| format!("{}: {}", uci, error))?;
+    }
+    Ok(game)
+}
+
+/// `go` with any of `wtime`, `btime`, `winc`, `binc`, `movestogo`, `depth`, `nodes`, `mate`,
+/// `movetime`, `infinite` and `ponder`. Also returns whether to ponder. `searchmoves` is not
+/// supported, and the moves after it are skipped like any other unknown token.
+fn parse_go(args: &[&str]) -> (SearchLimits, bool) {
+    let mut limits = SearchLimits::default();
+    let mut ponder = false;
+    let mut tokens = args.iter();
+
+    while let Some(token) = tokens.next() {
+        let mut value = || tokens.next().copied();
+        match *token {
+            "wtime" => {
+                limits.wtime = millis(value());
+            }
+            "btime" => {
+                limits.btime = millis(value());
+            }
+            "winc" => {
+                limits.winc = millis(value()).unwrap_or_default();
+            }
+            "binc" => {
+                limits.binc = millis(value()).unwrap_or_default();
+            }
+            "movestogo" => {
+                limits.movestogo = number(value());
+            }
+            "depth" => {
+                limits.depth = number(value()).unwrap_or(limits.depth);
+            }
+            // A mate in n moves is found within 2n - 1 plies
+            "mate" => {
+                if let Some(moves) = number::<u32>(value()) {
+                    limits.depth = (moves * 2).saturating_sub(1);
+                }
+            }
+            "nodes" => {
+                limits.nodes = number(value());
+            }
+            "movetime" => {
+                limits.movetime = millis(value());
+            }
+            "infinite" => {
+                limits.infinite = true;
+            }
+            "ponder" => {
+                ponder = true;
+            }
+            _ => {}
+        }
+    }
+    (limits, ponder)
+}
+
+pub(crate) fn number<T: FromStr>(token: Option<&str>) -> Option<T> {
+    token?.parse().ok()
+}
+
+/// Milliseconds, where GUIs sometimes send a negative time left after a late move.
+fn millis(token: Option<&str>) -> Option<Duration> {
+    number::<i64>(token).map(|millis| Duration::from_millis(millis.max(0) as u64))
+}
+
+fn info(result: &SearchResult, hashfull: u32) -> String {
+    let score = match mate_in(result.score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+    let mut info = format!(
+        "info depth {} score {} nodes {} nps {} hashfull {} time {}",
+        result.depth,
+        score,
+        result.nodes,
+        result.nps(),
+        hashfull,
+        result.time.as_millis()
+    );
+    if !result.pv.is_empty() {
+        let pv = result.pv.iter().map(PMove::to_uci).collect::<Vec<_>>();
+        info.push_str(&format!(" pv {}", pv.join(" ")));
+    }
+    info
+}
+
+/// `bestmove` with the expected reply to ponder on, or the null move `0000` when there are no
+/// legal moves.
+fn best_move(result: &SearchResult) -> String {
+    match (result.best_move, result.pv.get(1)) {
+        (Some(best), Some(ponder)) => {
+            format!("bestmove {} ponder {}", best.to_uci(), ponder.to_uci())
+        }
+        (Some(best), None) => format!("bestmove {}", best.to_uci()),
+        (None, _) => "bestmove 0000".to_string(),
+    }
+}
@@ -0,0 +1,379 @@
+use crate::*;
+use std::io::BufRead;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::sync::{ Arc, Mutex };
+use std::thread::{ self, JoinHandle };
+use std::time::Duration;
+
+/// How long to think without a time control, a depth limit or a time per move.
+const DEFAULT_MOVETIME: Duration = Duration::from_secs(5);
+
+/// Speaks the Chess Engine Communication Protocol (xboard/WinBoard), reading commands from
+/// `input` and writing to `output` until `quit` or the end of the input. Unlike UCI the engine
+/// keeps the game itself and answers moves with its own when it is on move. Returns `output`
+/// once the last search has finished.
+pub fn xboard_loop<W: Write + Send + 'static>(input: impl BufRead, output: W) -> io::Result<W> {
+    let mut xboard = XBoard {
+        game: Game::default(),
+        tt: Arc::new(TranspositionTable::default()),
+        options: SearchOptions::default(),
+        output: Arc::new(Mutex::new(output)),
+        engine: Some(1),
+        post: false,
+        level: None,
+        movetime: None,
+        depth: None,
+        time: None,
+        otim: None,
+        search: None,
+    };
+
+    for line in input.lines() {
+        if !xboard.handle(&line?)? {
+            xboard.cancel()?;
+            break;
+        }
+    }
+    xboard.wait()?;
+
+    match Arc::try_unwrap(xboard.output) {
+        Ok(output) => Ok(output.into_inner().unwrap_or_else(|error| error.into_inner())),
+        Err(_) => unreachable!("search threads are joined"),
+    }
+}
+
+/// A time control from `level`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Level {
+    /// Moves per time control, or `None` for sudden death.
+    moves: Option<u32>,
+    base: Duration,
+    increment: Duration,
+}
+
+struct XBoard<W> {
+    game: Game,
+    tt: Arc<TranspositionTable>,
+    options: SearchOptions,
+    output: Arc<Mutex<W>>,
+    /// The side the engine plays, or `None` in force mode.
+    engine: Option<u8>,
+    /// Whether to send thinking output.
+    post: bool,
+    level: Option<Level>,
+    /// Time per move from `st`.
+    movetime: Option<Duration>,
+    depth: Option<u32>,
+    /// The engine's and the opponent's clocks, from `time` and `otim`.
+    time: Option<Duration>,
+    otim: Option<Duration>,
+    search: Option<Thinking>,
+}
+
+/// A search for the engine's move running on its own thread. The thread sends the move and
+/// returns it, so it can be played on the game too.
+struct Thinking {
+    control: Arc<SearchControl>,
+    /// Set with the output locked when the move is no longer wanted, so the move is either sent
+    /// and returned or neither.
+    cancelled: Arc<AtomicBool>,
+    handle: JoinHandle<io::Result<Option<PMove>>>,
+}
+
+impl<W: Write + Send + 'static> XBoard<W> {
+    /// Handles one line of input, returning false on `quit`.
+    fn handle(&mut self, line: &str) -> io::Result<bool> {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let Some((command, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+        if self.search.as_ref().is_some_and(|search| search.handle.is_finished()) {
+            self.wait()?;
+        }
+
+        match *command {
+            "protover" => {
+                self.send(
+                    &format!(
+                        "feature myname=\"chessrs {}\" ping=1 setboard=1 usermove=1 time=1 \
+                         draw=0 sigint=0 sigterm=0 reuse=1 analyze=0 colors=0 memory=1 smp=1",
+                        env!("CARGO_PKG_VERSION")
+                    )
+                )?;
+                self.send("feature done=1")?;
+            }
+            "new" => {
+                self.cancel()?;
+                self.game = Game::default();
+                self.engine = Some(1);
+                self.depth = None;
+                self.tt.clear();
+            }
+            "force" | "result" => {
+                self.cancel()?;
+                self.engine = None;
+            }
+            "go" => {
+                self.wait()?;
+                self.engine = Some(self.game.turn);
+                self.think();
+            }
+            "usermove" =>
+                match args.first() {
+                    Some(uci) => self.user_move(uci)?,
+                    None => self.send("Error (no move given): usermove")?,
+                }
+            "level" =>
+                match parse_level(args) {
+                    Some(level) => {
+                        self.level = Some(level);
+                        self.movetime = None;
+                    }
+                    None => self.send(&format!("Error (bad time control): {}", line))?,
+                }
+            "st" =>
+                match args.first().and_then(|seconds| seconds.parse::<f64>().ok()) {
+                    Some(seconds) => {
+                        self.movetime = Some(Duration::from_secs_f64(seconds.max(0.0)));
+                    }
+                    None => self.send(&format!("Error (bad time per move): {}", line))?,
+                }
+            "sd" => {
+                self.depth = number(args.first().copied());
+            }
+            "time" => {
+                self.time = centiseconds(args.first().copied());
+            }
+            "otim" => {
+                self.otim = centiseconds(args.first().copied());
+            }
+            "undo" | "remove" => {
+                self.cancel()?;
+                self.game.takeback(if *command == "undo" { 1 } else { 2 });
+            }
+            "setboard" => {
+                self.cancel()?;
+                match Game::from_fen(&args.join(" ")) {
+                    Ok(game) => {
+                        self.game = game;
+                    }
+                    Err(error) => {
+                        self.send(&format!("tellusererror Illegal position: {}", error))?;
+                    }
+                }
+            }
+            "post" => {
+                self.post = true;
+            }
+            "nopost" => {
+                self.post = false;
+            }
+            "ping" => self.send(&format!("pong {}", args.first().unwrap_or(&"")))?,
+            // Move now
+            "?" => {
+                if let Some(search) = &self.search {
+                    search.control.stop();
+                }
+            }
+            "memory" => {
+                if let Some(megabytes) = number::<usize>(args.first().copied()) {
+                    let megabytes = megabytes.clamp(1, MAX_HASH_MB);
+                    self.tt = Arc::new(TranspositionTable::new(megabytes));
+                }
+            }
+            "cores" => {
+                if let Some(threads) = number::<usize>(args.first().copied()) {
+                    self.options.threads = threads.clamp(1, MAX_THREADS);
+                }
+            }
+            "quit" => {
+                return Ok(false);
+            }
+            // Accepted, but nothing to do for this engine
+            "xboard" | "accepted" | "rejected" | "random" | "easy" | "hard" | "computer" | "name" |
+            "rating" | "ics" | "white" | "black" => {}
+            // Before protocol version 2 moves are sent without `usermove`
+            _ if is_coordinate_move(command) => self.user_move(command)?,
+            _ => self.send(&format!("Error (unknown command): {}", line))?,
+        }
+        Ok(true)
+    }
+
+    fn send(&self, line: &str) -> io::Result<()> {
+        send(&self.output, line)
+    }
+
+    /// Plays the opponent's move and answers it if the engine is on move.
+    fn user_move(&mut self, uci: &str) -> io::Result<()> {
+        self.wait()?;
+        let played = self.game.parse_uci(uci).and_then(|pmove| self.game.make_pmove(pmove));
+        if played.is_err() {
+            return self.send(&format!("Illegal move: {}", uci));
+        }
+
+        if let Some(result) = game_result(&self.game) {
+            self.send(result)?;
+        } else if self.engine == Some(self.game.turn) {
+            self.think();
+        }
+        Ok(())
+    }
+
+    /// Starts searching for the engine's move, unless the game is over.
+    fn think(&mut self) {
+        if game_result(&self.game).is_some() {
+            return;
+        }
+
+        let limits = self.limits();
+        let control = Arc::new(SearchControl::default());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (mut game, options, post) = (self.game.clone(), self.options, self.post);
+        let (tt, output) = (self.tt.clone(), self.output.clone());
+        let (search_control, search_cancelled) = (control.clone(), cancelled.clone());
+        let handle = thread::spawn(move || {
+            let mut sent = Ok(());
+            let mut report = |result: &SearchResult| {
+                if post && sent.is_ok() {
+                    sent = send(&output, &thinking(result));
+                }
+            };
+            let result = search_with_control(
+                &mut game,
+                limits,
+                options,
+                &tt,
+                &search_control,
+                &mut report
+            );
+            sent?;
+
+            let Some(best) = result.best_move else {
+                return Ok(None);
+            };
+            let mut output = output.lock().unwrap_or_else(|error| error.into_inner());
+            if search_cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            writeln!(output, "move {}", best.to_uci())?;
+            game.make_pmove(best).expect("searched moves are legal");
+            if let Some(result) = game_result(&game) {
+                writeln!(output, "{}", result)?;
+            }
+            output.flush()?;
+            Ok(Some(best))
+        });
+
+        self.search = Some(Thinking { control, cancelled, handle });
+    }
+
+    /// Search limits for the side to move from the time control, `st` and `sd`.
+    fn limits(&self) -> SearchLimits {
+        let mut limits = SearchLimits {
+            depth: self.depth.unwrap_or(MAX_DEPTH),
+            ..SearchLimits::default()
+        };
+        if self.movetime.is_some() {
+            limits.movetime = self.movetime;
+            return limits;
+        }
+
+        let base = self.level.map(|level| level.base);
+        let (ours, theirs) = (self.time.or(base), self.otim.or(base));
+        if ours.is_none() && self.depth.is_none() {
+            limits.movetime = Some(DEFAULT_MOVETIME);
+            return limits;
+        }
+        (limits.wtime, limits.btime) = if self.game.turn == 0 {
+            (ours, theirs)
+        } else {
+            (theirs, ours)
+        };
+        if let Some(level) = self.level {
+            (limits.winc, limits.binc) = (level.increment, level.increment);
+            limits.movestogo = level.moves.map(|moves| {
+                moves - (self.game.fullmove_number.saturating_sub(1) % moves)
+            });
+        }
+        limits
+    }
+
+    /// Waits for the engine's move, if it is thinking, and plays it.
+    fn wait(&mut self) -> io::Result<()> {
+        if let Some(search) = self.search.take() {
+            if let Some(pmove) = search.handle.join().expect("search thread panicked")? {
+                self.game.make_pmove(pmove).expect("searched moves are legal");
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the engine thinking without sending its move. A move that was already sent is
+    /// still played.
+    fn cancel(&mut self) -> io::Result<()> {
+        if let Some(search) = &self.search {
+            let _output = self.output.lock().unwrap_or_else(|error| error.into_inner());
+            search.cancelled.store(true, Ordering::Relaxed);
+            search.control.stop();
+        }
+        self.wait()
+    }
+}
+
+/// `level MPS BASE INC`, with the base time in minutes or `minutes:seconds` and the
+/// increment in seconds.
+fn parse_level(args: &[&str]) -> Option<Level> {
+    let moves = number::<u32>(args.first().copied())?;
+    let base = args.get(1)?;
+    let (minutes, seconds) = base.split_once(':').unwrap_or((base, "0"));
+    let seconds = minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?;
+    let base = Duration::from_secs(seconds);
+    let increment = Duration::from_secs_f64(number::<f64>(args.get(2).copied())?.max(0.0));
+    Some(Level { moves: Some(moves).filter(|moves| *moves > 0), base, increment })
+}
+
+/// Whether `s` has the shape of a move in coordinate notation, like `e2e4` or `a7a8q`.
+fn is_coordinate_move(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    (bytes.len() == 4 || bytes.len() == 5) &&
+        bytes[..4].chunks(2).all(|square| {
+            (b'a'..=b'h').contains(&square[0]) && (b'1'..=b'8').contains(&square[1])
+        })
+}
+
+/// Clock times are sent in centiseconds, and may be negative after a late move.
+fn centiseconds(token: Option<&str>) -> Option<Duration> {
+    number::<i64>(token).map(|centiseconds| {
+        Duration::from_millis((centiseconds.max(0) as u64) * 10)
+    })
+}
+
+/// The result command for a finished game.
+fn game_result(game: &Game) -> Option<&'static str> {
+    if game.check_win() {
+        Some(if game.turn == 0 { "0-1 {Black mates}" } else { "1-0 {White mates}" })
+    } else if game.check_draw() {
+        Some("1/2-1/2 {Draw}")
+    } else {
+        None
+    }
+}
+
+/// Thinking output: depth, score, time in centiseconds, nodes and the principal variation.
+/// Mate scores are sent as 100000 plus the moves to mate, as xboard shows them.
+fn thinking(result: &SearchResult) -> String {
+    let score = match mate_in(result.score) {
+        Some(moves) if moves > 0 => 100_000 + moves,
+        Some(moves) => -100_000 + moves,
+        None => result.score,
+    };
+    let pv = result.pv.iter().map(PMove::to_uci).collect::<Vec<_>>();
+    format!(
+        "{} {} {} {} {}",
+        result.depth,
+        score,
+        result.time.as_millis() / 10,
+        result.nodes,
+        pv.join(" ")
+    )
+}